@@ -0,0 +1,179 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+
+use crate::{
+    config::Config,
+    led::{
+        font::Rasterization, font_registry::is_scalable, Display, FontRegistry, LedFont,
+        TextOverflow,
+    },
+    simulator::{export::ExportStyle, led_canvas::LedCanvas, terminal::render_frame},
+};
+
+// Glyphs per line when previewing the printable ASCII characters
+const PREVIEW_GLYPHS_PER_LINE: usize = 16;
+
+// Pixel size TrueType/OpenType fonts are loaded at to count their glyphs
+const LIST_SCALABLE_SIZE: f32 = 12.0;
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum FontsCommand {
+    /// List the BDF, TrueType and OpenType fonts in a directory
+    List {
+        /// Directory to search, `display.font_dir` by default
+        dir: Option<PathBuf>,
+    },
+    /// Render a font in the terminal or into a PNG image
+    Preview {
+        /// Font name in `display.font_dir` or path, with a pixel size for TrueType/OpenType
+        /// fonts like `DejaVuSansMono.ttf:12`
+        font: String,
+        /// Text to render instead of the printable ASCII characters
+        #[arg(short, long)]
+        text: Option<String>,
+        /// Image to write instead of printing to the terminal
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Width & height of a single LED in image pixels
+        #[arg(long, default_value_t = 8)]
+        scale: u32,
+    },
+}
+
+pub(crate) fn run(config: &Config, command: FontsCommand) -> Result<()> {
+    match command {
+        FontsCommand::List { dir } => list(config, dir.as_deref()),
+        FontsCommand::Preview {
+            font,
+            text,
+            output,
+            scale,
+        } => preview(config, &font, text, output, scale),
+    }
+}
+
+/// Prints the size and glyph count of every font, marking the ones used by the config.
+fn list(config: &Config, dir: Option<&Path>) -> Result<()> {
+    let dir = dir.unwrap_or(&config.display.font_dir);
+    let mut paths = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            is_scalable(path) || path.extension().is_some_and(|extension| extension == "bdf")
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let fonts = FontRegistry::new(&config.display.font_dir);
+    let used = [
+        ("display.font_large", &config.display.font_large),
+        ("display.font_small", &config.display.font_small),
+    ]
+    .map(|(key, name)| (key, fs::canonicalize(fonts.path(name)).ok()));
+
+    for path in paths {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let font = if is_scalable(&path) {
+            LedFont::new_scalable(&path, LIST_SCALABLE_SIZE, Rasterization::default())
+        } else {
+            LedFont::new(&path)
+        };
+        let font = match font {
+            Ok(font) => font,
+            Err(e) => {
+                println!("{:<20} {}", name, e);
+                continue;
+            }
+        };
+
+        let size = if is_scalable(&path) {
+            "scalable".to_string()
+        } else {
+            let (width, height) = font.bounds();
+            format!("{:>3}x{:<3}", width, height)
+        };
+        let mut line = format!("{:<20} {:>8} {:>4} glyphs", name, size, font.glyph_count());
+        let canonical = fs::canonicalize(&path).ok();
+        for (key, _) in used.iter().filter(|(_, used)| *used == canonical) {
+            line.push_str(&format!("  ({})", key));
+        }
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+fn preview(
+    config: &Config,
+    name: &str,
+    text: Option<String>,
+    output: Option<PathBuf>,
+    scale: u32,
+) -> Result<()> {
+    let font = FontRegistry::new(&config.display.font_dir)
+        .with_fallbacks(config.display.font_fallbacks.clone())
+        .get(name)?;
+
+    let lines = match text {
+        Some(text) => {
+            if let Some(c) = text.chars().find(|&c| !font.contains(c)) {
+                bail!("Font {:?} has no glyph for {:?}", name, c);
+            }
+            vec![text]
+        }
+        None => {
+            let chars = (' '..='~')
+                .filter(|&c| font.contains(c))
+                .collect::<Vec<_>>();
+            chars
+                .chunks(PREVIEW_GLYPHS_PER_LINE)
+                .map(|line| line.iter().collect())
+                .collect()
+        }
+    };
+
+    // One LED of margin around the text
+    let (_, line_height) = font.bounds();
+    let width = lines
+        .iter()
+        .map(|line| font.measure(line).0)
+        .max()
+        .unwrap_or(0) as u32
+        + 2;
+    let height = lines.len() as u32 * line_height + 2;
+
+    let mut canvas = LedCanvas::new(height, width);
+    for (row, line) in lines.iter().enumerate() {
+        let y = 1 + (row as u32 * line_height) as i32 + font.ascent();
+        canvas.draw_text(
+            &font,
+            line,
+            1,
+            y,
+            &config.display.color.into(),
+            0,
+            false,
+            TextOverflow::Clip,
+        );
+    }
+
+    match output {
+        Some(output) => {
+            let style = ExportStyle {
+                scale,
+                led_dots: true,
+            };
+            canvas.save_png(&output, style)?;
+            println!("Saved preview to {}", output.display());
+        }
+        None => print!(
+            "{}",
+            render_frame(&canvas.pixel_buffer.lock().unwrap(), width, height)
+        ),
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+//! Command-line interface.
+//!
+//! Besides running the display, the subcommands exercise single parts of it (rendering,
+//! fonts, the weather API) so they can be debugged without changing any code.
+
+use std::path::PathBuf;
+
+#[cfg(not(feature = "hardware"))]
+use clap::Args;
+use clap::{Parser, Subcommand};
+
+#[cfg(not(feature = "hardware"))]
+use crate::config::Config;
+#[cfg(feature = "simulator")]
+use crate::config::WindowScale;
+
+pub(crate) mod fonts;
+pub(crate) mod snapshot;
+pub(crate) mod weather;
+
+/// Clock and weather display for RGB LED panels
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub(crate) struct Cli {
+    /// Config file, defaults to `RUSTYLED_CONFIG` or `config.toml`
+    #[arg(short, long, global = true, value_name = "PATH")]
+    pub(crate) config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum Command {
+    /// Run the display on the backend this binary was built for (default)
+    Run,
+    /// Run the display in the simulator window and/or terminal
+    #[cfg(not(feature = "hardware"))]
+    Simulate(SimulateArgs),
+    /// Render a single frame into a PNG image
+    Snapshot(snapshot::SnapshotArgs),
+    /// Inspect BDF, TTF and OTF fonts
+    Fonts {
+        #[command(subcommand)]
+        command: fonts::FontsCommand,
+    },
+    /// Query the weather API
+    Weather {
+        #[command(subcommand)]
+        command: weather::WeatherCommand,
+    },
+}
+
+#[cfg(not(feature = "hardware"))]
+#[derive(Args, Debug)]
+pub(crate) struct SimulateArgs {
+    /// Draw the panel in the terminal, always on without the `simulator` feature
+    #[arg(long)]
+    terminal: bool,
+    /// Window scale factor (1, 2, 4, 8, 16, 32 or fit)
+    #[cfg(feature = "simulator")]
+    #[arg(long)]
+    scale: Option<WindowScale>,
+}
+
+#[cfg(not(feature = "hardware"))]
+impl SimulateArgs {
+    /// Overrides the `[simulator]` settings of the config file.
+    pub(crate) fn apply(self, mut config: Config) -> Config {
+        // Without a window the terminal is the only way to see the simulator
+        config.simulator.terminal |= self.terminal || cfg!(not(feature = "simulator"));
+        #[cfg(feature = "simulator")]
+        if let Some(scale) = self.scale {
+            config.simulator.scale = scale;
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::Cli;
+
+    #[test]
+    fn cli_is_valid() {
+        Cli::command().debug_assert();
+    }
+}
@@ -0,0 +1,128 @@
+use std::{path::PathBuf, time::Duration};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{NaiveDateTime, NaiveTime};
+use clap::Args;
+
+use crate::{
+    config::Config,
+    led::FontRegistry,
+    simulator::{export::ExportStyle, led_canvas::LedCanvas, pixel_mapper::PixelMap},
+    weather_api::api::{get_api_details, WeatherApiResponse},
+};
+
+#[derive(Args, Debug)]
+pub(crate) struct SnapshotArgs {
+    /// Time to show, e.g. `14:35`, `14:35:42` or `2024-03-15T14:35:42` [default: now]
+    #[arg(long, value_name = "TIME", value_parser = parse_time)]
+    at: Option<NaiveDateTime>,
+    /// Weather API response (JSON file) to show instead of fetching the current weather
+    #[arg(long, value_name = "JSON")]
+    weather: Option<PathBuf>,
+    /// Image to write
+    #[arg(short, long, default_value = "snapshot.png")]
+    output: PathBuf,
+    /// Width & height of a single LED in image pixels
+    #[arg(long, default_value_t = 1)]
+    scale: u32,
+    /// Draw every LED as a round dot, from a scale of 3 upwards
+    #[arg(long)]
+    dots: bool,
+    /// Show the frame the way the chained panels receive it, with the pixel mappers applied
+    #[arg(long)]
+    physical: bool,
+}
+
+/// Renders the frame the display would show at the given time into a PNG image.
+pub(crate) fn run(config: &Config, args: SnapshotArgs) -> Result<()> {
+    let mut fonts = FontRegistry::new(&config.display.font_dir)
+        .with_fallbacks(config.display.font_fallbacks.clone());
+    let font_lg = crate::load_font(&mut fonts, "display.font_large", &config.display.font_large)?;
+    let font_sm = crate::load_font(&mut fonts, "display.font_small", &config.display.font_small)?;
+
+    let weather: WeatherApiResponse = match &args.weather {
+        Some(path) => {
+            let json = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            serde_json::from_str(&json)
+                .with_context(|| format!("Invalid weather response {}", path.display()))?
+        }
+        None => get_api_details().context("Failed to fetch the weather")?,
+    };
+    let now = args
+        .at
+        .unwrap_or_else(|| chrono::Local::now().naive_local());
+
+    let (matrix_width, matrix_height) = config.matrix.size();
+    let pixel_map = PixelMap::new(
+        &config.matrix.pixel_mapper_config,
+        config.matrix.chain_length.get(),
+        config.matrix.parallel.get(),
+        matrix_width,
+        matrix_height,
+    )
+    .map_err(|e| anyhow!("matrix.pixel_mapper_config: {}", e))?;
+
+    let mut canvas = LedCanvas::new(pixel_map.height, pixel_map.width);
+    // A still frame shows scrolling text and text effects at their start
+    crate::Dashboard::new(pixel_map.width, pixel_map.height, font_lg, font_sm).draw(
+        &mut canvas,
+        &config.display,
+        &weather,
+        now,
+        Duration::ZERO,
+    );
+
+    if args.physical {
+        let frame = pixel_map.to_matrix(&canvas.pixel_buffer.lock().unwrap());
+        canvas = LedCanvas::new(matrix_height, matrix_width);
+        *canvas.pixel_buffer.lock().unwrap() = frame;
+    }
+
+    let style = ExportStyle {
+        scale: args.scale,
+        led_dots: args.dots,
+    };
+    canvas.save_png(&args.output, style)?;
+    println!("Saved snapshot to {}", args.output.display());
+    Ok(())
+}
+
+/// Parses a date & time, or a time of today.
+fn parse_time(value: &str) -> Result<NaiveDateTime, String> {
+    let datetime = ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok());
+    if let Some(datetime) = datetime {
+        return Ok(datetime);
+    }
+
+    ["%H:%M:%S", "%H:%M"]
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(value, format).ok())
+        .map(|time| chrono::Local::now().date_naive().and_time(time))
+        .ok_or_else(|| "expected a time like 14:35, 14:35:42 or 2024-03-15T14:35:42".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Timelike};
+
+    use super::parse_time;
+
+    #[test]
+    fn parse_time_accepts_dates_and_times() {
+        assert_eq!(
+            parse_time("2024-03-15T14:35:42").unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 15)
+                .unwrap()
+                .and_hms_opt(14, 35, 42)
+                .unwrap()
+        );
+
+        let time = parse_time("14:35").unwrap();
+        assert_eq!((time.hour(), time.minute(), time.second()), (14, 35, 0));
+
+        assert!(parse_time("2:35pm").is_err());
+    }
+}
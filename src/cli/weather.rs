@@ -0,0 +1,24 @@
+use anyhow::Result;
+use clap::Subcommand;
+
+use crate::weather_api::{api::get_api_details, WeatherCondition};
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum WeatherCommand {
+    /// Fetch the current weather and print the parsed response
+    Fetch,
+}
+
+pub(crate) fn run(command: WeatherCommand) -> Result<()> {
+    match command {
+        WeatherCommand::Fetch => {
+            let weather = get_api_details()?;
+            println!("{:#?}", weather);
+            println!(
+                "Condition: {:?}",
+                WeatherCondition::from(weather.current.weather_code)
+            );
+        }
+    }
+    Ok(())
+}
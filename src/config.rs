@@ -0,0 +1,427 @@
+use std::{
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+
+use crate::{
+    led::{
+        paint::{Edge, Fill},
+        LedColor, TextPaint,
+    },
+    led_color,
+    simulator::pixel_mapper::PixelMap,
+};
+
+/// Config file used when neither `--config` nor `RUSTYLED_CONFIG` is given
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+/// Settings read from the TOML config file.
+///
+/// Every key is optional and falls back to the defaults below. Values are validated while
+/// deserializing, so errors point at the offending key in the file.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Config {
+    pub(crate) matrix: MatrixConfig,
+    pub(crate) runtime: RuntimeConfig,
+    pub(crate) display: DisplayConfig,
+    pub(crate) weather: WeatherConfig,
+    pub(crate) simulator: SimulatorConfig,
+}
+
+/// Feeds `LedMatrixOptions`
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct MatrixConfig {
+    /// Rows of a single panel
+    pub(crate) rows: NonZeroU32,
+    /// Columns of a single panel
+    pub(crate) cols: NonZeroU32,
+    pub(crate) hardware_mapping: String,
+    /// Panels daisy-chained together
+    pub(crate) chain_length: NonZeroU32,
+    /// Parallel chains
+    pub(crate) parallel: NonZeroU32,
+    pub(crate) pwm_bits: u8,
+    /// Brightness in percent
+    pub(crate) brightness: u8,
+    pub(crate) scan_mode: u32,
+    pub(crate) led_rgb_sequence: String,
+    pub(crate) pixel_mapper_config: String,
+    pub(crate) row_addr_type: u32,
+    pub(crate) multiplexing: u32,
+    pub(crate) inverse_colors: bool,
+}
+
+impl Default for MatrixConfig {
+    fn default() -> Self {
+        Self {
+            rows: NonZeroU32::new(64).unwrap(),
+            cols: NonZeroU32::new(64).unwrap(),
+            hardware_mapping: "adafruit-hat".to_string(),
+            chain_length: NonZeroU32::new(1).unwrap(),
+            parallel: NonZeroU32::new(1).unwrap(),
+            pwm_bits: 11,
+            brightness: 100,
+            scan_mode: 0,
+            led_rgb_sequence: "RGB".to_string(),
+            pixel_mapper_config: String::new(),
+            row_addr_type: 0,
+            multiplexing: 0,
+            inverse_colors: false,
+        }
+    }
+}
+
+impl MatrixConfig {
+    /// Width & height of all chained and parallel panels, before the pixel mappers are applied
+    pub(crate) fn size(&self) -> (u32, u32) {
+        (
+            self.cols.get() * self.chain_length.get(),
+            self.rows.get() * self.parallel.get(),
+        )
+    }
+
+    /// Checks the options rpi-led-matrix only rejects once the matrix is created, with an
+    /// error that does not name the option.
+    pub(crate) fn validate(&self) -> Result<()> {
+        let sequence = self.led_rgb_sequence.to_ascii_uppercase();
+        if sequence.len() != 3 || !"RGB".chars().all(|c| sequence.contains(c)) {
+            bail!(
+                "matrix.led_rgb_sequence: expected an ordering of R, G and B, found {:?}",
+                self.led_rgb_sequence
+            );
+        }
+
+        let (width, height) = self.size();
+        PixelMap::new(
+            &self.pixel_mapper_config,
+            self.chain_length.get(),
+            self.parallel.get(),
+            width,
+            height,
+        )
+        .map_err(|e| anyhow!("matrix.pixel_mapper_config: {}", e))?;
+        Ok(())
+    }
+}
+
+/// Feeds `LedRuntimeOptions`
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct RuntimeConfig {
+    pub(crate) gpio_slowdown: u32,
+    pub(crate) drop_privileges: bool,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            gpio_slowdown: 4,
+            drop_privileges: true,
+        }
+    }
+}
+
+/// Settings of the draw loop
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct DisplayConfig {
+    /// Time between two frames, animations like scrolling text advance every frame
+    pub(crate) refresh_rate_ms: NonZeroU32,
+    pub(crate) color: LedColor,
+    /// Directory fonts are looked up in by name
+    pub(crate) font_dir: PathBuf,
+    /// Font name in `font_dir` or path, TrueType/OpenType fonts with a size like `x.ttf:12`
+    pub(crate) font_large: String,
+    pub(crate) font_small: String,
+    /// Fonts searched in order for characters missing from the large & small font
+    pub(crate) font_fallbacks: Vec<String>,
+    /// Effects of the time & date
+    pub(crate) clock_style: TextStyle,
+    /// Effects of the weather readings
+    pub(crate) weather_style: TextStyle,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            refresh_rate_ms: NonZeroU32::new(40).unwrap(),
+            color: led_color!("#2EC866"),
+            font_dir: PathBuf::from("fonts"),
+            font_large: "6x12".to_string(),
+            font_small: "5x8".to_string(),
+            font_fallbacks: Vec::new(),
+            clock_style: TextStyle::default(),
+            weather_style: TextStyle::default(),
+        }
+    }
+}
+
+/// Colors & effects of a piece of text, see [`TextPaint`]
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct TextStyle {
+    /// Falls back to a solid `display.color`
+    pub(crate) fill: Option<Fill>,
+    pub(crate) edge: Option<Edge>,
+    /// Period of blinking, the text is hidden for the second half
+    pub(crate) blink_ms: Option<NonZeroU32>,
+}
+
+impl TextStyle {
+    /// The paint of this style `elapsed` after the effects started.
+    pub(crate) fn paint(&self, color: LedColor, elapsed: Duration) -> TextPaint {
+        TextPaint {
+            fill: self.fill.unwrap_or(Fill::Solid(color)),
+            edge: self.edge,
+            blink: self
+                .blink_ms
+                .map(|period| Duration::from_millis(period.get() as u64)),
+            elapsed,
+        }
+    }
+}
+
+/// Settings of the weather poller
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct WeatherConfig {
+    pub(crate) interval_secs: NonZeroU32,
+}
+
+impl Default for WeatherConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: NonZeroU32::new(300).unwrap(),
+        }
+    }
+}
+
+/// Settings of the simulator outputs, ignored on hardware
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+#[allow(dead_code)]
+pub(crate) struct SimulatorConfig {
+    /// Window scale factor, or `fit`
+    pub(crate) scale: WindowScale,
+    /// Also draw the panel in the terminal
+    pub(crate) terminal: bool,
+}
+
+impl Default for SimulatorConfig {
+    fn default() -> Self {
+        Self {
+            scale: WindowScale::Factor(16),
+            terminal: false,
+        }
+    }
+}
+
+/// A window scale supported by minifb
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "ScaleValue")]
+#[allow(dead_code)]
+pub(crate) enum WindowScale {
+    /// 1, 2, 4, 8, 16 or 32 screen pixels per LED
+    Factor(u32),
+    /// As large as fits on the screen, written as `fit`
+    Fit,
+}
+
+/// A scale as written in the config file, a number or `"fit"`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ScaleValue {
+    Factor(u32),
+    Name(String),
+}
+
+impl TryFrom<ScaleValue> for WindowScale {
+    type Error = String;
+
+    fn try_from(scale: ScaleValue) -> Result<Self, Self::Error> {
+        match scale {
+            ScaleValue::Factor(factor) => Self::try_from(factor),
+            ScaleValue::Name(name) => name.parse(),
+        }
+    }
+}
+
+impl TryFrom<u32> for WindowScale {
+    type Error = String;
+
+    fn try_from(scale: u32) -> Result<Self, Self::Error> {
+        match scale {
+            1 | 2 | 4 | 8 | 16 | 32 => Ok(Self::Factor(scale)),
+            _ => Err(format!(
+                "unsupported scale {}, expected 1, 2, 4, 8, 16, 32 or fit",
+                scale
+            )),
+        }
+    }
+}
+
+impl FromStr for WindowScale {
+    type Err = String;
+
+    fn from_str(scale: &str) -> Result<Self, Self::Err> {
+        if scale == "fit" {
+            return Ok(Self::Fit);
+        }
+        let scale = scale
+            .parse::<u32>()
+            .map_err(|_| format!("invalid scale {}", scale))?;
+        Self::try_from(scale)
+    }
+}
+
+impl Config {
+    /// Loads the config file given with `--config <path>` or `RUSTYLED_CONFIG`.
+    ///
+    /// Without either, `config.toml` in the working directory is used if it exists,
+    /// otherwise the defaults.
+    pub(crate) fn load(path: Option<&Path>) -> Result<Self> {
+        if let Some(path) = path {
+            return Self::from_file(path);
+        }
+        if let Ok(path) = dotenv::var("RUSTYLED_CONFIG") {
+            return Self::from_file(Path::new(&path));
+        }
+
+        let path = Path::new(DEFAULT_CONFIG_PATH);
+        if path.exists() {
+            Self::from_file(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub(crate) fn from_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Config, WindowScale};
+    use crate::led::paint::{Edge, Fill};
+
+    #[test]
+    fn missing_keys_fall_back_to_defaults() {
+        let config: Config = toml::from_str("[matrix]\ncols = 128\n").unwrap();
+
+        assert_eq!(config.matrix.cols.get(), 128);
+        assert_eq!(config.matrix.rows.get(), 64);
+        assert_eq!(config.matrix.hardware_mapping, "adafruit-hat");
+        assert_eq!(config.weather.interval_secs.get(), 300);
+    }
+
+    #[test]
+    fn invalid_values_point_at_the_key() {
+        let error = toml::from_str::<Config>("[display]\ncolor = \"green\"\n")
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("line 2"), "{}", error);
+        assert!(error.contains("expected a hex color"), "{}", error);
+    }
+
+    #[test]
+    fn invalid_matrix_options_name_the_key() {
+        let error = |toml: &str| {
+            let config = toml::from_str::<Config>(toml).unwrap();
+            config.matrix.validate().unwrap_err().to_string()
+        };
+
+        let mapper = error("[matrix]\npixel_mapper_config = \"Rotate:45\"\n");
+        assert!(
+            mapper.starts_with("matrix.pixel_mapper_config: "),
+            "{}",
+            mapper
+        );
+        assert!(mapper.contains("not a multiple of 90"), "{}", mapper);
+        let sequence = error("[matrix]\nled_rgb_sequence = \"RGR\"\n");
+        assert!(
+            sequence.starts_with("matrix.led_rgb_sequence: "),
+            "{}",
+            sequence
+        );
+
+        let config = toml::from_str::<Config>("[matrix]\nled_rgb_sequence = \"bgr\"\n").unwrap();
+        assert!(config.matrix.validate().is_ok());
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let error = toml::from_str::<Config>("[matrix]\nwidth = 64\n")
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("unknown field `width`"), "{}", error);
+    }
+
+    #[test]
+    fn window_scales_are_factors_or_fit() {
+        let scale = |toml: &str| toml::from_str::<Config>(toml).map(|c| c.simulator.scale);
+
+        assert_eq!(scale("").unwrap(), WindowScale::Factor(16));
+        assert_eq!(
+            scale("[simulator]\nscale = 4\n").unwrap(),
+            WindowScale::Factor(4)
+        );
+        assert_eq!(
+            scale("[simulator]\nscale = \"fit\"\n").unwrap(),
+            WindowScale::Fit
+        );
+        assert!(scale("[simulator]\nscale = 3\n").is_err());
+        assert_eq!("fit".parse::<WindowScale>().unwrap(), WindowScale::Fit);
+    }
+
+    #[test]
+    fn text_styles_are_parsed() {
+        let config: Config = toml::from_str(
+            "[display.clock_style]\n\
+             fill = { horizontal_gradient = [\"#FF0000\", \"#0000FF\"] }\n\
+             edge = { shadow = \"#000000\" }\n\
+             blink_ms = 1000\n\
+             [display.weather_style]\n\
+             fill = { rainbow = { width = 32, speed = 8.0 } }\n",
+        )
+        .unwrap();
+        let elapsed = Duration::from_millis(1500);
+
+        let clock = config
+            .display
+            .clock_style
+            .paint(config.display.color, elapsed);
+        assert!(matches!(clock.fill, Fill::HorizontalGradient(..)));
+        assert!(matches!(clock.edge, Some(Edge::Shadow(_))));
+        assert_eq!(clock.blink, Some(Duration::from_secs(1)));
+        assert!(!clock.visible());
+
+        let weather = config
+            .display
+            .weather_style
+            .paint(config.display.color, elapsed);
+        assert!(matches!(weather.fill, Fill::Rainbow { width: 32, .. }));
+        assert!(weather.edge.is_none() && weather.visible());
+
+        // Without a style, text keeps the display color
+        let plain = Config::default()
+            .display
+            .clock_style
+            .paint(config.display.color, elapsed);
+        assert!(matches!(plain.fill, Fill::Solid(_)));
+        assert!(plain.edge.is_none() && plain.visible());
+    }
+}
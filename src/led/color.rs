@@ -0,0 +1,200 @@
+use std::str::FromStr;
+
+use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+use serde::{de, Deserialize, Deserializer};
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LedColor {
+    pub(crate) red: u8,
+    pub(crate) green: u8,
+    pub(crate) blue: u8,
+}
+
+impl LedColor {
+    pub(crate) fn zero() -> Self {
+        Self {
+            red: 0,
+            green: 0,
+            blue: 0,
+        }
+    }
+
+    /// This color with the given opacity
+    pub(crate) fn with_alpha(self, alpha: u8) -> LedRgba {
+        LedRgba {
+            red: self.red,
+            green: self.green,
+            blue: self.blue,
+            alpha,
+        }
+    }
+
+    /// The fully saturated color of `hue`, where 0.0 is red, 1/3 green and 2/3 blue.
+    ///
+    /// Hues wrap around, so 1.0 is red again.
+    pub(crate) fn from_hue(hue: f32) -> Self {
+        let sector = hue.rem_euclid(1.0) * 6.0;
+        let channel = |offset: f32| {
+            // Full for a third of the circle, fading in and out over the sixths next to it
+            let distance = (sector - offset).rem_euclid(6.0);
+            let level = match distance {
+                d if d < 1.0 => 1.0,
+                d if d < 2.0 => 2.0 - d,
+                d if d < 4.0 => 0.0,
+                d if d < 5.0 => d - 4.0,
+                _ => 1.0,
+            };
+            (level * 255.0).round() as u8
+        };
+        Self {
+            red: channel(0.0),
+            green: channel(2.0),
+            blue: channel(4.0),
+        }
+    }
+
+    /// Mixes `color` over this one, an `alpha` of 0.0 keeps this color and 1.0 replaces it.
+    pub(crate) fn blend(self, color: LedColor, alpha: f32) -> Self {
+        let alpha = alpha.clamp(0.0, 1.0);
+        let mix =
+            |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * alpha).round() as u8;
+        Self {
+            red: mix(self.red, color.red),
+            green: mix(self.green, color.green),
+            blue: mix(self.blue, color.blue),
+        }
+    }
+}
+
+/// A color with opacity, an `alpha` of 0 is fully transparent and 255 opaque.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LedRgba {
+    pub(crate) red: u8,
+    pub(crate) green: u8,
+    pub(crate) blue: u8,
+    pub(crate) alpha: u8,
+}
+
+impl LedRgba {
+    pub(crate) fn transparent() -> Self {
+        Self {
+            red: 0,
+            green: 0,
+            blue: 0,
+            alpha: 0,
+        }
+    }
+
+    /// The color without its opacity
+    pub(crate) fn color(self) -> LedColor {
+        LedColor {
+            red: self.red,
+            green: self.green,
+            blue: self.blue,
+        }
+    }
+
+    /// Composites this color over `below`, like stacking two sheets of tinted glass.
+    pub(crate) fn over(self, below: LedRgba) -> Self {
+        let above_alpha = self.alpha as f32 / 255.0;
+        let below_alpha = below.alpha as f32 / 255.0 * (1.0 - above_alpha);
+        let alpha = above_alpha + below_alpha;
+        if alpha <= 0.0 {
+            return Self::transparent();
+        }
+
+        let mix = |above: u8, below: u8| {
+            ((above as f32 * above_alpha + below as f32 * below_alpha) / alpha).round() as u8
+        };
+        Self {
+            red: mix(self.red, below.red),
+            green: mix(self.green, below.green),
+            blue: mix(self.blue, below.blue),
+            alpha: (alpha * 255.0).round() as u8,
+        }
+    }
+}
+
+impl From<LedColor> for LedRgba {
+    fn from(color: LedColor) -> Self {
+        color.with_alpha(0xFF)
+    }
+}
+
+/// Packed as `0xAARRGGBB`
+impl From<LedRgba> for u32 {
+    fn from(color: LedRgba) -> Self {
+        ((color.alpha as u32) << 24) | u32::from(color.color())
+    }
+}
+
+impl From<u32> for LedRgba {
+    fn from(color: u32) -> Self {
+        LedColor::from(color).with_alpha((color >> 24) as u8)
+    }
+}
+
+impl From<LedColor> for u32 {
+    fn from(color: LedColor) -> Self {
+        ((color.red as u32) << 16) | ((color.green as u32) << 8) | (color.blue as u32)
+    }
+}
+
+impl From<u32> for LedColor {
+    fn from(color: u32) -> Self {
+        Self {
+            red: ((color >> 16) & 0xFF) as u8,
+            green: ((color >> 8) & 0xFF) as u8,
+            blue: (color & 0xFF) as u8,
+        }
+    }
+}
+
+impl From<Rgb888> for LedColor {
+    fn from(color: Rgb888) -> Self {
+        Self {
+            red: color.r(),
+            green: color.g(),
+            blue: color.b(),
+        }
+    }
+}
+
+impl From<LedColor> for Rgb888 {
+    fn from(color: LedColor) -> Self {
+        Rgb888::new(color.red, color.green, color.blue)
+    }
+}
+
+impl From<LedColor> for String {
+    fn from(color: LedColor) -> Self {
+        format!("#{:02X}{:02X}{:02X}", color.red, color.green, color.blue)
+    }
+}
+
+impl FromStr for LedColor {
+    type Err = String;
+
+    /// Parses a hex color such as `#2EC866` (the `#` is optional).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        if hex.len() != 6 {
+            return Err(format!(
+                "expected a hex color like \"#2EC866\", found {:?}",
+                s
+            ));
+        }
+
+        u32::from_str_radix(hex, 16)
+            .map(Into::into)
+            .map_err(|_| format!("expected a hex color like \"#2EC866\", found {:?}", s))
+    }
+}
+
+impl<'de> Deserialize<'de> for LedColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
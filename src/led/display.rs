@@ -0,0 +1,1021 @@
+use super::{
+    font::GlyphBitmap,
+    paint::{Brush, TextPaint},
+    LedColor, LedFont,
+};
+
+/// What happens to text running past an edge of the canvas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TextOverflow {
+    /// Pixels outside the canvas are dropped
+    Clip,
+    /// Pixels continue on the opposite edge, e.g. for scrolling text
+    // The widgets scroll text through a marquee, which clips it to its box instead
+    #[cfg_attr(not(test), allow(dead_code))]
+    Wrap,
+}
+
+/// Horizontal position of text within its box
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HorizontalAlign {
+    Left,
+    Center,
+    // The time is centered and all other text starts at the left of its box
+    #[cfg_attr(not(test), allow(dead_code))]
+    Right,
+}
+
+/// Vertical position of text within its box
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VerticalAlign {
+    /// The ascent of the font touches the top of the box
+    Top,
+    /// Ascent & descent are centered in the box
+    Middle,
+    /// The baseline lies on the bottom of the box, descenders hang below it
+    // Widgets lay out their lines from the top, using the font height
+    #[cfg_attr(not(test), allow(dead_code))]
+    Baseline,
+}
+
+/// Drawing surface shared by all backends.
+///
+/// Widgets are written against this trait so they render the same on the
+/// simulator canvas and on a physical panel driven by rpi-led-matrix.
+pub(crate) trait Display {
+    /// Retrieves the width & height of the canvas
+    #[must_use]
+    fn canvas_size(&self) -> (i32, i32);
+
+    /// Sets the pixel at the given coordinate to the given color.
+    fn set(&mut self, x: i32, y: i32, color: &LedColor);
+
+    /// Retrieves the color drawn at the given coordinate, `None` outside the canvas.
+    #[must_use]
+    fn get(&self, x: i32, y: i32) -> Option<LedColor>;
+
+    /// Mixes `color` into the pixel at the given coordinate.
+    ///
+    /// An `alpha` of 0.0 leaves the pixel untouched, 1.0 replaces it like [`Display::set`].
+    fn blend(&mut self, x: i32, y: i32, color: &LedColor, alpha: f32) {
+        if alpha <= 0.0 {
+            return;
+        }
+        if let Some(current) = self.get(x, y) {
+            self.set(x, y, &current.blend(*color, alpha));
+        }
+    }
+
+    /// Clears the canvas.
+    fn clear(&mut self) {
+        self.fill(&LedColor::zero())
+    }
+
+    /// Fills the canvas with the given color.
+    fn fill(&mut self, color: &LedColor);
+
+    /// Draws a straight, one pixel wide line.
+    fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: &LedColor) {
+        // Bresenham's line algorithm
+        let mut x0 = x0;
+        let mut y0 = y0;
+        let dx = (x1 - x0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let dy = -(y1 - y0).abs();
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        loop {
+            self.set(x0, y0, color);
+            if x0 == x1 && y0 == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x0 += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y0 += sy;
+            }
+        }
+    }
+
+    /// Draws a one pixel wide circle.
+    // No widget draws outlined circles yet, e.g. an analog clock face
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn draw_circle(&mut self, x: i32, y: i32, radius: u32, color: &LedColor) {
+        for (dx, dy) in circle_octant(radius) {
+            for (a, b) in [(dx, dy), (dy, dx)] {
+                self.set(x + a, y + b, color);
+                self.set(x + a, y - b, color);
+                self.set(x - a, y + b, color);
+                self.set(x - a, y - b, color);
+            }
+        }
+    }
+
+    /// Draws an anti-aliased line with Xiaolin Wu's algorithm.
+    ///
+    /// Endpoints may lie between pixels. Every pixel is blended by how much of the line
+    /// covers it, so a line between pixel centers lights its endpoints fully.
+    // Meant for smooth clock hands and graphs, which no widget draws yet
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn draw_line_aa(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: &LedColor) {
+        // Walk along the major axis, steep lines are traced with swapped coordinates
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        let (x0, y0, x1, y1) = if steep {
+            (y0, x0, y1, x1)
+        } else {
+            (x0, y0, x1, y1)
+        };
+        let (x0, y0, x1, y1) = if x0 > x1 {
+            (x1, y1, x0, y0)
+        } else {
+            (x0, y0, x1, y1)
+        };
+        let gradient = if x1 > x0 { (y1 - y0) / (x1 - x0) } else { 0.0 };
+
+        for column in x0.floor() as i32..=x1.ceil() as i32 {
+            let center = column as f32;
+            // The line reaches half a pixel past its endpoints
+            let coverage = (center.min(x1) - center.max(x0) + 1.0).min(1.0);
+            let y = y0 + gradient * (center.clamp(x0, x1) - x0);
+            let (row, fraction) = (y.floor(), y - y.floor());
+            for (row, alpha) in [(row, 1.0 - fraction), (row + 1.0, fraction)] {
+                let (x, y) = if steep {
+                    (row as i32, column)
+                } else {
+                    (column, row as i32)
+                };
+                self.blend(x, y, color, alpha * coverage);
+            }
+        }
+    }
+
+    /// Draws an anti-aliased circle with Xiaolin Wu's algorithm, the center may lie between pixels.
+    ///
+    /// Every octant is stepped along its major axis, splitting each step between the two pixels
+    /// straddling the exact edge. A pixel reached from two octants at a diagonal is drawn once,
+    /// by the octant covering it the most.
+    // Round shapes at sub-pixel positions are not drawn by any widget yet
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn draw_circle_aa(&mut self, x: f32, y: f32, radius: f32, color: &LedColor) {
+        if radius < 0.0 {
+            return;
+        }
+
+        // Top & bottom are stepped column by column, left & right row by row, so the
+        // circle is never steeper than 45 degrees where it is traced
+        let diagonal = radius * std::f32::consts::FRAC_1_SQRT_2;
+        let columns = (x - diagonal).ceil() as i32..=(x + diagonal).floor() as i32;
+        let rows = (y - diagonal).ceil() as i32..=(y + diagonal).floor() as i32;
+
+        // Coverage of the pixel at `minor` on the line `major` by the octants traced along
+        // that axis, with the circle centered at `(center, other)` in those coordinates
+        let coverage = |major: i32, minor: i32, center: f32, other: f32| {
+            let offset = (radius.powi(2) - (major as f32 - center).powi(2))
+                .max(0.0)
+                .sqrt();
+            [other - offset, other + offset]
+                .into_iter()
+                .filter_map(|edge| {
+                    let (pixel, fraction) = (edge.floor() as i32, edge - edge.floor());
+                    match minor - pixel {
+                        0 => Some(1.0 - fraction),
+                        1 => Some(fraction),
+                        _ => None,
+                    }
+                })
+                .reduce(f32::max)
+        };
+        let vertical = |column: i32, row: i32| {
+            columns
+                .contains(&column)
+                .then(|| coverage(column, row, x, y))
+                .flatten()
+        };
+        let horizontal = |column: i32, row: i32| {
+            rows.contains(&row)
+                .then(|| coverage(row, column, y, x))
+                .flatten()
+        };
+
+        for column in columns.clone() {
+            let offset = (radius.powi(2) - (column as f32 - x).powi(2))
+                .max(0.0)
+                .sqrt();
+            for edge in [y - offset, y + offset] {
+                let (row, fraction) = (edge.floor() as i32, edge - edge.floor());
+                for (row, alpha) in [(row, 1.0 - fraction), (row + 1, fraction)] {
+                    if horizontal(column, row).is_none_or(|other| alpha >= other) {
+                        self.blend(column, row, color, alpha);
+                    }
+                }
+            }
+        }
+        for row in rows.clone() {
+            let offset = (radius.powi(2) - (row as f32 - y).powi(2)).max(0.0).sqrt();
+            for edge in [x - offset, x + offset] {
+                let (column, fraction) = (edge.floor() as i32, edge - edge.floor());
+                for (column, alpha) in [(column, 1.0 - fraction), (column + 1, fraction)] {
+                    if vertical(column, row).is_none_or(|other| alpha > other) {
+                        self.blend(column, row, color, alpha);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draws a one pixel wide rectangle outline with its top-left corner at `(x, y)`.
+    // No widget has a frame yet
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn draw_rect(&mut self, x: i32, y: i32, width: u32, height: u32, color: &LedColor) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let (right, bottom) = (x + width as i32 - 1, y + height as i32 - 1);
+        self.draw_line(x, y, right, y, color);
+        self.draw_line(x, bottom, right, bottom, color);
+        self.draw_line(x, y, x, bottom, color);
+        self.draw_line(right, y, right, bottom, color);
+    }
+
+    /// Fills a rectangle with its top-left corner at `(x, y)`.
+    fn fill_rect(&mut self, x: i32, y: i32, width: u32, height: u32, color: &LedColor) {
+        for row in y..y + height as i32 {
+            draw_span(self, x, x + width as i32 - 1, row, color);
+        }
+    }
+
+    /// Fills a circle, including the outline drawn by [`Display::draw_circle`].
+    // Filled dots, e.g. for a status indicator, are not drawn yet
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn fill_circle(&mut self, x: i32, y: i32, radius: u32, color: &LedColor) {
+        for (dx, dy) in circle_octant(radius) {
+            draw_span(self, x - dx, x + dx, y - dy, color);
+            draw_span(self, x - dx, x + dx, y + dy, color);
+            draw_span(self, x - dy, x + dy, y - dx, color);
+            draw_span(self, x - dy, x + dy, y + dx, color);
+        }
+        self.draw_circle(x, y, radius, color);
+    }
+
+    /// Draws a one pixel wide rectangle outline with rounded corners.
+    ///
+    /// The corner radius is limited to half the width or height.
+    // Rounded frames around widgets are not drawn yet
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn draw_rounded_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        radius: u32,
+        color: &LedColor,
+    ) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let radius = radius.min((width - 1) / 2).min((height - 1) / 2) as i32;
+        let (right, bottom) = (x + width as i32 - 1, y + height as i32 - 1);
+
+        self.draw_line(x + radius, y, right - radius, y, color);
+        self.draw_line(x + radius, bottom, right - radius, bottom, color);
+        self.draw_line(x, y + radius, x, bottom - radius, color);
+        self.draw_line(right, y + radius, right, bottom - radius, color);
+
+        // Quarter circles around the inner corners
+        let (left, top) = (x + radius, y + radius);
+        let (right, bottom) = (right - radius, bottom - radius);
+        for (dx, dy) in circle_octant(radius as u32) {
+            for (a, b) in [(dx, dy), (dy, dx)] {
+                self.set(left - a, top - b, color);
+                self.set(right + a, top - b, color);
+                self.set(left - a, bottom + b, color);
+                self.set(right + a, bottom + b, color);
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    /// Draws the one pixel wide outline of a triangle.
+    // Triangle outlines, e.g. for trend arrows, are not drawn yet
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn draw_triangle(
+        &mut self,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        color: &LedColor,
+    ) {
+        self.draw_polygon(&[(x0, y0), (x1, y1), (x2, y2)], color);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    /// Fills a triangle, including the outline drawn by [`Display::draw_triangle`].
+    // Solid arrowheads are not drawn yet
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn fill_triangle(
+        &mut self,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        color: &LedColor,
+    ) {
+        self.fill_polygon(&[(x0, y0), (x1, y1), (x2, y2)], color);
+    }
+
+    /// Draws lines connecting the points in order.
+    // Meant for temperature graphs, which are not drawn yet
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn draw_polyline(&mut self, points: &[(i32, i32)], color: &LedColor) {
+        if let [(x, y)] = points {
+            self.set(*x, *y, color);
+        }
+        for pair in points.windows(2) {
+            let [(x0, y0), (x1, y1)] = [pair[0], pair[1]];
+            self.draw_line(x0, y0, x1, y1, color);
+        }
+    }
+
+    /// Draws the one pixel wide outline of a polygon, closing it from the last to the first point.
+    // No widget draws outlined shapes yet, e.g. weather icons drawn as vectors
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn draw_polygon(&mut self, points: &[(i32, i32)], color: &LedColor) {
+        self.draw_polyline(points, color);
+        if let (Some(&(x0, y0)), Some(&(x1, y1))) = (points.last(), points.first()) {
+            self.draw_line(x0, y0, x1, y1, color);
+        }
+    }
+
+    /// Fills a polygon with a scanline fill, including the outline drawn by
+    /// [`Display::draw_polygon`].
+    ///
+    /// Self-intersecting polygons are filled with the even-odd rule, so overlapping parts
+    /// are left open.
+    // No widget fills polygons yet, e.g. for a wind direction needle
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn fill_polygon(&mut self, points: &[(i32, i32)], color: &LedColor) {
+        let Some(top) = points.iter().map(|&(_, y)| y).min() else {
+            return;
+        };
+        let bottom = points.iter().map(|&(_, y)| y).max().unwrap_or(top);
+        let (_, height) = self.canvas_size();
+
+        let mut crossings = Vec::new();
+        for y in top.max(0)..=bottom.min(height - 1) {
+            crossings.clear();
+            for (i, &(x0, y0)) in points.iter().enumerate() {
+                let (x1, y1) = points[(i + 1) % points.len()];
+                // Half-open edges so a vertex shared by two edges is only counted once
+                if (y0 <= y && y < y1) || (y1 <= y && y < y0) {
+                    crossings
+                        .push(x0 as f32 + (y - y0) as f32 * (x1 - x0) as f32 / (y1 - y0) as f32);
+                }
+            }
+            crossings.sort_by(f32::total_cmp);
+
+            for span in crossings.chunks_exact(2) {
+                draw_span(
+                    self,
+                    span[0].ceil() as i32,
+                    span[1].floor() as i32,
+                    y,
+                    color,
+                );
+            }
+        }
+
+        self.draw_polygon(points, color);
+    }
+
+    /// Draws the pixels of a glyph with the top-left corner of its bitmap at `(x, y)`, colored
+    /// by `brush`.
+    ///
+    /// The brush is sampled where a pixel would be without wrapping. Anti-aliased pixels are
+    /// blended with the pixel below. Backends with a locked pixel buffer override this to
+    /// blit whole rows under one lock.
+    fn draw_bitmap(
+        &mut self,
+        glyph: &GlyphBitmap,
+        x: i32,
+        y: i32,
+        brush: &Brush,
+        overflow: TextOverflow,
+    ) {
+        let (width, height) = self.canvas_size();
+        for row in 0..glyph.height {
+            for column in glyph.columns(row) {
+                let (x, y) = (x + column as i32, y + row as i32);
+                let color = brush.color(x, y);
+                let (x, y) = match overflow {
+                    TextOverflow::Clip => (x, y),
+                    TextOverflow::Wrap => (x.rem_euclid(width), y.rem_euclid(height)),
+                };
+                match glyph.alpha(column, row) {
+                    255 => self.set(x, y, &color),
+                    alpha => self.blend(x, y, &color, alpha as f32 / 255.0),
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    /// Renders text with the given font, starting at `(x, y)` on the baseline.
+    ///
+    /// Glyphs are placed by their bounding box offsets and advanced by their `DWIDTH`, like
+    /// rpi-led-matrix does. Text is laid out per grapheme, so accents stay on their letter,
+    /// see [`LedFont::cluster`]. Characters missing from the font and its fallbacks are drawn
+    /// with its replacement glyph, see [`LedFont::glyph`]. Vertical text runs top to bottom,
+    /// one font height per grapheme.
+    ///
+    /// `paint` spreads its fill over the line from the ascent to the descent, and draws its
+    /// outline or shadow behind all of the text. Blinking text that is hidden draws nothing.
+    ///
+    /// Returns the advance: the width of horizontal or the height of vertical text,
+    /// including `kerning_offset` after every grapheme.
+    fn draw_text(
+        &mut self,
+        font: &LedFont,
+        text: &str,
+        x: i32,
+        y: i32,
+        paint: &TextPaint,
+        kerning_offset: i32,
+        vertical: bool,
+        overflow: TextOverflow,
+    ) -> i32 {
+        let kerning_offset = kerning_offset.max(0);
+        let clusters: Vec<_> = font
+            .clusters(text)
+            .filter(|cluster| cluster.base.is_some())
+            .collect();
+
+        // Glyphs are placed up front, edges are drawn behind the whole text
+        let (mut pen_x, mut pen_y) = (x, y);
+        let mut glyphs = Vec::new();
+        for cluster in &clusters {
+            for (offset, glyph) in cluster.glyphs() {
+                glyphs.push((pen_x + offset + glyph.left, pen_y + glyph.top, glyph));
+            }
+
+            if vertical {
+                pen_y += font.bounds().1 as i32 + kerning_offset;
+            } else {
+                pen_x += cluster.advance + kerning_offset;
+            }
+        }
+        let advance = if vertical { pen_y - y } else { pen_x - x };
+
+        if !paint.visible() {
+            return advance;
+        }
+
+        if let Some(edge) = paint.edge {
+            let (color, offsets) = edge.offsets();
+            let brush = Brush::solid(color);
+            for (dx, dy) in offsets {
+                for (left, top, glyph) in &glyphs {
+                    self.draw_bitmap(glyph, left + dx, top + dy, &brush, overflow);
+                }
+            }
+        }
+
+        let size = if vertical {
+            (font.bounds().0 as i32, advance)
+        } else {
+            (advance, font.ascent() + font.descent())
+        };
+        let brush = Brush::new(paint.fill, (x, y - font.ascent()), size, paint.elapsed);
+        for (left, top, glyph) in &glyphs {
+            self.draw_bitmap(glyph, *left, *top, &brush, overflow);
+        }
+
+        advance
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    /// Renders a line of text aligned within the box with its top-left corner at `(x, y)`.
+    ///
+    /// Text larger than the box overflows it. Returns the advance like [`Display::draw_text`].
+    fn draw_text_aligned(
+        &mut self,
+        font: &LedFont,
+        text: &str,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        horizontal: HorizontalAlign,
+        vertical: VerticalAlign,
+        paint: &TextPaint,
+    ) -> i32 {
+        let (text_width, text_height) = font.measure(text);
+        let x = match horizontal {
+            HorizontalAlign::Left => x,
+            HorizontalAlign::Center => x + (width as i32 - text_width) / 2,
+            HorizontalAlign::Right => x + width as i32 - text_width,
+        };
+        let baseline = match vertical {
+            VerticalAlign::Top => y + font.ascent(),
+            VerticalAlign::Middle => y + (height as i32 - text_height) / 2 + font.ascent(),
+            VerticalAlign::Baseline => y + height as i32,
+        };
+        self.draw_text(font, text, x, baseline, paint, 0, false, TextOverflow::Clip)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    /// Renders text word-wrapped to the box with its top-left corner at `(x, y)`.
+    ///
+    /// Lines are the font height plus `line_spacing` apart. When the text does not fit, the
+    /// last line that does ends with an ellipsis. Returns the number of lines drawn.
+    // Multi-line text, e.g. a weather description, is not shown yet
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn draw_text_box(
+        &mut self,
+        font: &LedFont,
+        text: &str,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        line_spacing: u32,
+        horizontal: HorizontalAlign,
+        paint: &TextPaint,
+    ) -> usize {
+        let line_height = (font.ascent() + font.descent()).max(0) as u32;
+        // The spacing is only needed between lines
+        let max_lines = ((height + line_spacing) / (line_height + line_spacing).max(1)) as usize;
+
+        let mut lines = font.wrap(text, width as i32);
+        if lines.len() > max_lines {
+            lines.truncate(max_lines);
+            if let Some(last) = lines.last_mut() {
+                *last = font.ellipsize(last, width as i32);
+            }
+        }
+
+        for (row, line) in lines.iter().enumerate() {
+            self.draw_text_aligned(
+                font,
+                line,
+                x,
+                y + (row as u32 * (line_height + line_spacing)) as i32,
+                width,
+                line_height,
+                horizontal,
+                VerticalAlign::Top,
+                paint,
+            );
+        }
+        lines.len()
+    }
+
+    /// Presents everything drawn since the previous swap.
+    fn swap(&mut self);
+}
+
+/// Sets the pixels from `x0` to `x1` (inclusive) on row `y`, clipped to the canvas.
+fn draw_span<D: Display + ?Sized>(display: &mut D, x0: i32, x1: i32, y: i32, color: &LedColor) {
+    let (width, height) = display.canvas_size();
+    if y < 0 || y >= height {
+        return;
+    }
+    for x in x0.max(0)..=x1.min(width - 1) {
+        display.set(x, y, color);
+    }
+}
+
+/// Offsets of one octant of a midpoint circle, from the top `(0, radius)` to the diagonal.
+// Shared by the circle & rounded rectangle primitives, which no widget uses yet
+#[cfg_attr(not(test), allow(dead_code))]
+fn circle_octant(radius: u32) -> Vec<(i32, i32)> {
+    let mut points = Vec::new();
+    let (mut x, mut y) = (0, radius as i32);
+    let mut d = 3 - 2 * radius as i32;
+    while x <= y {
+        points.push((x, y));
+        if d < 0 {
+            d += 4 * x + 6;
+        } else {
+            d += 4 * (x - y) + 10;
+            y -= 1;
+        }
+        x += 1;
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::{Display, HorizontalAlign, TextOverflow, VerticalAlign};
+    use crate::{
+        led::{
+            font::Rasterization,
+            layer::Layer,
+            paint::{Edge, Fill},
+            LedColor, LedFont, TextPaint,
+        },
+        led_color,
+        simulator::{
+            led_canvas::LedCanvas,
+            snapshot::{assert_snapshot, font, fonts_dir},
+        },
+    };
+
+    // A concave, five-pointed star
+    const STAR: [(i32, i32); 10] = [
+        (48, 34),
+        (51, 43),
+        (61, 43),
+        (53, 49),
+        (56, 59),
+        (48, 53),
+        (40, 59),
+        (43, 49),
+        (35, 43),
+        (45, 43),
+    ];
+
+    #[test]
+    fn shapes_match_snapshot() {
+        let mut canvas = LedCanvas::new(64, 64);
+
+        canvas.draw_rect(1, 1, 20, 14, &led_color!("#FF0000"));
+        canvas.fill_rect(4, 4, 14, 8, &led_color!("#2EC866"));
+        canvas.draw_rounded_rect(23, 1, 18, 14, 4, &led_color!("#3080FF"));
+        canvas.fill_circle(52, 8, 7, &led_color!("#FFD700"));
+        canvas.draw_triangle(1, 31, 10, 17, 19, 31, &led_color!("#FFFFFF"));
+        canvas.fill_triangle(22, 31, 41, 18, 34, 31, &led_color!("#FF00FF"));
+        canvas.draw_polyline(
+            &[(1, 40), (6, 34), (11, 40), (16, 34), (21, 40), (26, 34)],
+            &led_color!("#00FFFF"),
+        );
+        canvas.draw_polygon(
+            &[(1, 62), (8, 45), (30, 50), (20, 62)],
+            &led_color!("#FF8000"),
+        );
+        canvas.fill_polygon(&STAR, &led_color!("#FFD700"));
+
+        assert_snapshot("shapes", &canvas);
+    }
+
+    #[test]
+    fn filled_shapes_cover_their_outline() {
+        let outline = led_color!("#FF0000");
+        let fill = led_color!("#00FF00");
+        let mut canvas = LedCanvas::new(64, 64);
+
+        canvas.draw_rect(2, 2, 10, 7, &outline);
+        canvas.fill_rect(2, 2, 10, 7, &fill);
+        canvas.draw_circle(30, 10, 8, &outline);
+        canvas.fill_circle(30, 10, 8, &fill);
+        canvas.draw_triangle(2, 60, 20, 25, 29, 52, &outline);
+        canvas.fill_triangle(2, 60, 20, 25, 29, 52, &fill);
+        canvas.draw_polygon(&STAR, &outline);
+        canvas.fill_polygon(&STAR, &fill);
+
+        let outline: u32 = outline.into();
+        assert!(!canvas.pixel_buffer.lock().unwrap().contains(&outline));
+    }
+
+    #[test]
+    fn shapes_are_clipped_to_the_canvas() {
+        let color = LedColor::from(0xFFFFFF);
+        let mut canvas = LedCanvas::new(8, 8);
+
+        canvas.fill_rect(-4, -4, 100, 100, &color);
+        canvas.fill_polygon(&[(-10, -10), (20, -10), (5, 30)], &color);
+        canvas.fill_circle(4, 4, 40, &color);
+
+        assert!(canvas
+            .pixel_buffer
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|&pixel| pixel == 0xFFFFFF));
+    }
+
+    #[test]
+    fn antialiasing_matches_snapshot() {
+        let mut canvas = LedCanvas::new(64, 64);
+
+        // Spokes at every 15 degrees, like the hands of an analog clock
+        for step in 0..24 {
+            let angle = (step as f32 * 15.0).to_radians();
+            canvas.draw_line_aa(
+                16.0,
+                16.0,
+                16.0 + 14.0 * angle.sin(),
+                16.0 - 14.0 * angle.cos(),
+                &led_color!("#2EC866"),
+            );
+        }
+        canvas.draw_circle_aa(47.0, 16.0, 13.0, &led_color!("#FFD700"));
+        canvas.draw_circle_aa(47.5, 16.5, 5.5, &led_color!("#3080FF"));
+
+        // Lines over a filled background blend with what is already drawn
+        canvas.fill_rect(2, 36, 60, 26, &led_color!("#400040"));
+        canvas.draw_line_aa(2.0, 60.0, 61.0, 38.0, &led_color!("#FFFFFF"));
+        canvas.draw_line_aa(2.0, 38.0, 61.5, 52.25, &led_color!("#00FFFF"));
+        canvas.draw_circle_aa(31.5, 49.0, 10.0, &led_color!("#FF8000"));
+
+        assert_snapshot("antialiasing", &canvas);
+    }
+
+    #[test]
+    fn blend_mixes_with_the_current_pixel() {
+        let mut canvas = LedCanvas::new(4, 4);
+        canvas.fill(&led_color!("#204060"));
+
+        canvas.blend(0, 0, &led_color!("#FFFFFF"), 0.5);
+        canvas.blend(1, 0, &led_color!("#FFFFFF"), 0.0);
+        canvas.blend(2, 0, &led_color!("#FFFFFF"), 1.0);
+        canvas.blend(-1, 0, &led_color!("#FFFFFF"), 1.0);
+
+        let pixel = |x| u32::from(canvas.get(x, 0).unwrap());
+        assert_eq!(pixel(0), 0x90A0B0);
+        assert_eq!(pixel(1), 0x204060);
+        assert_eq!(pixel(2), 0xFFFFFF);
+        assert!(canvas.get(-1, 0).is_none());
+    }
+
+    #[test]
+    fn antialiased_lines_light_their_endpoints() {
+        let color = led_color!("#FFFFFF");
+        let mut canvas = LedCanvas::new(8, 8);
+
+        canvas.draw_line_aa(1.0, 1.0, 6.0, 1.0, &color);
+        canvas.draw_line_aa(1.0, 3.0, 6.0, 6.0, &color);
+
+        for x in 1..=6 {
+            assert_eq!(u32::from(canvas.get(x, 1).unwrap()), 0xFFFFFF);
+        }
+        assert_eq!(u32::from(canvas.get(0, 1).unwrap()), 0);
+        assert_eq!(u32::from(canvas.get(1, 3).unwrap()), 0xFFFFFF);
+        assert_eq!(u32::from(canvas.get(6, 6).unwrap()), 0xFFFFFF);
+    }
+
+    /// Draws white text with its baseline at `y` and returns the advance.
+    fn text(canvas: &mut LedCanvas, font: &LedFont, text: &str, (x, y): (i32, i32)) -> i32 {
+        let white = led_color!("#FFFFFF").into();
+        canvas.draw_text(font, text, x, y, &white, 0, false, TextOverflow::Clip)
+    }
+
+    fn pixels(canvas: &LedCanvas) -> Vec<u32> {
+        canvas.pixel_buffer.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn missing_glyphs_are_replaced() {
+        let font = font("5x8.bdf");
+        let (mut missing, mut replacement) = (LedCanvas::new(8, 8), LedCanvas::new(8, 8));
+
+        let advance = text(&mut missing, &font, "\u{E000}", (0, 7));
+        text(&mut replacement, &font, "\u{FFFD}", (0, 7));
+
+        assert_eq!(advance, 5);
+        assert_eq!(pixels(&missing), pixels(&replacement));
+    }
+
+    #[test]
+    fn text_returns_its_advance() {
+        let font = font("5x8.bdf");
+        let mut canvas = LedCanvas::new(64, 64);
+        let white = led_color!("#FFFFFF").into();
+
+        let horizontal = canvas.draw_text(&font, "abc", 0, 7, &white, 1, false, TextOverflow::Clip);
+        let vertical = canvas.draw_text(&font, "abc", 0, 7, &white, 1, true, TextOverflow::Clip);
+
+        assert_eq!(horizontal, 3 * (5 + 1));
+        assert_eq!(vertical, 3 * (8 + 1));
+    }
+
+    #[test]
+    fn vertical_text_runs_top_to_bottom() {
+        let font = font("5x8.bdf");
+        let white = led_color!("#FFFFFF").into();
+
+        // Every character of vertical text sits one font height below the previous one
+        let mut stacked = LedCanvas::new(16, 16);
+        text(&mut stacked, &font, "-", (2, 7));
+        text(&mut stacked, &font, "-", (2, 15));
+        let mut vertical = LedCanvas::new(16, 16);
+        vertical.draw_text(&font, "--", 2, 7, &white, 0, true, TextOverflow::Clip);
+
+        assert_eq!(pixels(&vertical), pixels(&stacked));
+    }
+
+    #[test]
+    fn text_is_clipped_or_wrapped_at_the_edges() {
+        let font = font("5x8.bdf");
+        let white = led_color!("#FFFFFF").into();
+        let count = |canvas: &LedCanvas| pixels(canvas).iter().filter(|&&pixel| pixel != 0).count();
+
+        let mut whole = LedCanvas::new(32, 32);
+        text(&mut whole, &font, "XX", (0, 7));
+        // Starting at (12, 10), the text runs past the right & bottom edges of the canvas
+        let mut clipped = LedCanvas::new(16, 16);
+        text(&mut clipped, &font, "XX", (12, 17));
+        let mut wrapped = LedCanvas::new(16, 16);
+        wrapped.draw_text(&font, "XX", 12, 17, &white, 0, false, TextOverflow::Wrap);
+
+        // Wrapped text continues at the top-left, so nothing is lost
+        assert!(count(&clipped) < count(&whole));
+        assert_eq!(count(&wrapped), count(&whole));
+        assert!(pixels(&clipped)[..16 * 10].iter().all(|&pixel| pixel == 0));
+        assert!(pixels(&wrapped)[..16 * 10].iter().any(|&pixel| pixel != 0));
+    }
+
+    #[test]
+    fn canvas_blits_glyphs_like_single_pixels() {
+        // Layers keep the default `draw_bitmap`, which sets one pixel at a time
+        let font = font("6x12.bdf");
+        let white = led_color!("#FFFFFF").into();
+
+        for overflow in [TextOverflow::Clip, TextOverflow::Wrap] {
+            let mut canvas = LedCanvas::new(16, 24);
+            canvas.draw_text(&font, "Wq°", 14, 18, &white, 1, false, overflow);
+            let mut layer = Layer::new(0, 0, 24, 16);
+            layer.draw_text(&font, "Wq°", 14, 18, &white, 1, false, overflow);
+
+            let layer_pixels = (0..16)
+                .flat_map(|y| (0..24).map(move |x| (x, y)))
+                .map(|(x, y)| u32::from(layer.get(x, y).unwrap()))
+                .collect::<Vec<_>>();
+            assert_eq!(pixels(&canvas), layer_pixels, "{:?}", overflow);
+        }
+    }
+
+    #[test]
+    fn glyphs_are_placed_by_their_metrics() {
+        // tom-thumb glyphs have their own bounding boxes and are 4 pixels apart
+        let font = font("tom-thumb.bdf");
+        let mut canvas = LedCanvas::new(8, 16);
+
+        let advance = text(&mut canvas, &font, "Tag.", (0, 6));
+
+        assert_eq!(advance, 16);
+        let pixels = pixels(&canvas);
+        let lit = |x: usize, y: usize| pixels[y * 16 + x] != 0;
+        let lit_columns = |y: usize| (0..16).filter(|&x| lit(x, y)).collect::<Vec<_>>();
+        // The cap height of 5 ends on the baseline, the tail of `g` reaches below it
+        assert!(lit_columns(0).is_empty());
+        assert!(lit_columns(1).iter().all(|&x| x < 4));
+        assert!(lit_columns(6).iter().all(|&x| (8..12).contains(&x)));
+        assert!(!lit_columns(6).is_empty());
+        assert!(lit_columns(7).is_empty());
+        // The period is offset one pixel into its cell
+        assert!(lit(13, 5) && !lit(12, 5));
+    }
+
+    #[test]
+    fn text_is_aligned_in_its_box() {
+        use super::{HorizontalAlign::*, VerticalAlign::*};
+
+        let font = font("5x8.bdf");
+        let white = led_color!("#FFFFFF").into();
+        let aligned = |horizontal, vertical| {
+            let mut canvas = LedCanvas::new(32, 32);
+            canvas.draw_text_aligned(&font, "abc", 4, 2, 24, 20, horizontal, vertical, &white);
+            pixels(&canvas)
+        };
+        // "abc" is 15 pixels wide, 7 above and 1 below the baseline
+        let expected = |x, baseline| {
+            let mut canvas = LedCanvas::new(32, 32);
+            text(&mut canvas, &font, "abc", (x, baseline));
+            pixels(&canvas)
+        };
+
+        assert_eq!(aligned(Left, Top), expected(4, 9));
+        assert_eq!(aligned(Center, Middle), expected(8, 15));
+        assert_eq!(aligned(Right, Baseline), expected(13, 22));
+    }
+
+    #[test]
+    fn text_box_matches_snapshot() {
+        let font = font("5x8.bdf");
+        let mut canvas = LedCanvas::new(64, 64);
+        let forecast = "Thunderstorm with slight hail, expect heavy rain later";
+
+        canvas.draw_rect(0, 0, 64, 30, &led_color!("#404040"));
+        let fitting = canvas.draw_text_box(
+            &font,
+            "Partly cloudy\nwith rain",
+            2,
+            2,
+            60,
+            26,
+            1,
+            HorizontalAlign::Center,
+            &led_color!("#2EC866").into(),
+        );
+        canvas.draw_rect(0, 32, 64, 32, &led_color!("#404040"));
+        let truncated = canvas.draw_text_box(
+            &font,
+            forecast,
+            2,
+            34,
+            60,
+            28,
+            2,
+            HorizontalAlign::Left,
+            &led_color!("#FFD700").into(),
+        );
+
+        assert_eq!((fitting, truncated), (3, 3));
+        assert_snapshot("text_box", &canvas);
+    }
+
+    #[test]
+    fn scalable_text_matches_snapshot() {
+        let path = fonts_dir().join("DejaVuSansMono.ttf");
+        let mono = LedFont::new_scalable(&path, 12.0, Rasterization::default()).unwrap();
+        let smooth = LedFont::new_scalable(&path, 12.0, Rasterization::Antialiased).unwrap();
+        let mut canvas = LedCanvas::new(32, 64);
+
+        let (center, top) = (HorizontalAlign::Center, VerticalAlign::Top);
+        let color = led_color!("#2EC866").into();
+        canvas.draw_text_aligned(&mono, "12:45", 0, 1, 64, 15, center, top, &color);
+        // Anti-aliased edges blend with the background
+        canvas.fill_rect(0, 16, 64, 16, &led_color!("#400040"));
+        canvas.draw_text_aligned(&smooth, "12:45", 0, 17, 64, 15, center, top, &color);
+
+        assert_snapshot("scalable_text", &canvas);
+    }
+
+    #[test]
+    fn unicode_text_matches_snapshot() {
+        let small = font("5x8.bdf");
+        let tiny = font("tom-thumb.bdf").with_fallbacks(vec![Arc::new(font("5x8.bdf"))]);
+        let mut canvas = LedCanvas::new(32, 64);
+
+        // A precomposed `ü`, and a `ǘ` stacked from `u` and its two marks
+        text(&mut canvas, &small, "Zu\u{308}rich ǘ", (1, 8));
+        text(&mut canvas, &small, "x\u{301}y\u{308}", (1, 18));
+        // Cyrillic from the fallback font, next to tom-thumb's own Latin-1
+        text(&mut canvas, &tiny, "Kraków Київ", (1, 28));
+
+        assert_snapshot("unicode_text", &canvas);
+    }
+
+    #[test]
+    fn text_paint_matches_snapshot() {
+        let font = font("6x12.bdf");
+        let mut canvas = LedCanvas::new(64, 64);
+        let (red, blue, black) = (
+            led_color!("#FF0000"),
+            led_color!("#0000FF"),
+            led_color!("#000000"),
+        );
+        let paint = |fill, edge| TextPaint {
+            fill,
+            edge,
+            blink: None,
+            elapsed: Duration::from_millis(500),
+        };
+        // Edges are drawn over a background to stand out from
+        canvas.fill_rect(0, 38, 64, 26, &led_color!("#2040A0"));
+        let mut draw = |y, paint: &TextPaint| {
+            canvas.draw_text(&font, "12:45", 2, y, paint, 1, false, TextOverflow::Clip)
+        };
+
+        draw(10, &paint(Fill::HorizontalGradient(red, blue), None));
+        draw(22, &paint(Fill::VerticalGradient(red, blue), None));
+        let rainbow = Fill::Rainbow {
+            width: 32,
+            speed: 8.0,
+        };
+        draw(34, &paint(rainbow, None));
+        let white = Fill::Solid(led_color!("#FFFFFF"));
+        draw(48, &paint(white, Some(Edge::Outline(black))));
+        draw(61, &paint(white, Some(Edge::Shadow(black))));
+
+        assert_snapshot("text_paint", &canvas);
+    }
+
+    #[test]
+    fn hidden_text_is_not_drawn() {
+        let font = font("5x8.bdf");
+        let mut canvas = LedCanvas::new(8, 32);
+        let mut paint = TextPaint::from(led_color!("#FFFFFF"));
+        paint.blink = Some(Duration::from_secs(1));
+        paint.elapsed = Duration::from_millis(700);
+
+        let advance = canvas.draw_text(&font, "abc", 0, 7, &paint, 0, false, TextOverflow::Clip);
+
+        // Blinking text keeps its place, so the layout does not jump
+        assert_eq!(advance, 15);
+        assert!(pixels(&canvas).iter().all(|&pixel| pixel == 0));
+    }
+}
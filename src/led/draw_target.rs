@@ -0,0 +1,119 @@
+//! embedded-graphics support, so its shapes, images (tinybmp, tinytga) and mono fonts can be
+//! drawn on every backend.
+//!
+//! `DrawTarget::clear` takes a color while [`Display::clear`] does not, call it as
+//! `DrawTarget::clear(&mut canvas, color)` when both traits are in scope.
+
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{OriginDimensions, Size},
+    pixelcolor::Rgb888,
+    primitives::Rectangle,
+    Pixel,
+};
+
+use super::Display;
+
+/// Implements [`DrawTarget`] and [`OriginDimensions`] on top of the [`Display`] implementation.
+macro_rules! impl_draw_target {
+    ($display:ty) => {
+        impl DrawTarget for $display {
+            type Color = Rgb888;
+            type Error = core::convert::Infallible;
+
+            fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+            where
+                I: IntoIterator<Item = Pixel<Self::Color>>,
+            {
+                for Pixel(point, color) in pixels {
+                    Display::set(self, point.x, point.y, &color.into());
+                }
+                Ok(())
+            }
+
+            fn fill_solid(
+                &mut self,
+                area: &Rectangle,
+                color: Self::Color,
+            ) -> Result<(), Self::Error> {
+                Display::fill_rect(
+                    self,
+                    area.top_left.x,
+                    area.top_left.y,
+                    area.size.width,
+                    area.size.height,
+                    &color.into(),
+                );
+                Ok(())
+            }
+
+            fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+                Display::fill(self, &color.into());
+                Ok(())
+            }
+        }
+
+        impl OriginDimensions for $display {
+            fn size(&self) -> Size {
+                let (width, height) = Display::canvas_size(self);
+                Size::new(width as u32, height as u32)
+            }
+        }
+    };
+}
+
+impl_draw_target!(crate::simulator::led_canvas::LedCanvas);
+#[cfg(not(feature = "hardware"))]
+impl_draw_target!(super::simulator::SimulatorDisplay);
+#[cfg(feature = "hardware")]
+impl_draw_target!(super::rpi::RpiDisplay);
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::{
+        image::Image,
+        mono_font::{ascii::FONT_6X10, MonoTextStyle},
+        pixelcolor::Rgb888,
+        prelude::*,
+        primitives::{Circle, PrimitiveStyle, PrimitiveStyleBuilder, Rectangle},
+        text::Text,
+    };
+    use tinybmp::Bmp;
+
+    use crate::{
+        led_color,
+        simulator::{led_canvas::LedCanvas, snapshot::assert_snapshot},
+    };
+
+    #[test]
+    fn embedded_graphics_matches_snapshot() {
+        let mut canvas = LedCanvas::new(32, 64);
+
+        Rectangle::new(Point::new(1, 1), Size::new(28, 15))
+            .into_styled(
+                PrimitiveStyleBuilder::new()
+                    .stroke_color(Rgb888::RED)
+                    .stroke_width(1)
+                    .fill_color(Rgb888::new(0, 0, 96))
+                    .build(),
+            )
+            .draw(&mut canvas)
+            .unwrap();
+        Circle::new(Point::new(32, 1), 15)
+            .into_styled(PrimitiveStyle::with_stroke(Rgb888::YELLOW, 2))
+            .draw(&mut canvas)
+            .unwrap();
+
+        let bmp = Bmp::<Rgb888>::from_slice(include_bytes!("../../bmp/ClearSky.bmp")).unwrap();
+        Image::new(&bmp, Point::new(48, 1))
+            .draw(&mut canvas)
+            .unwrap();
+
+        let style = MonoTextStyle::new(&FONT_6X10, led_color!("#2EC866").into());
+        Text::new("embedded", Point::new(2, 27), style)
+            .draw(&mut canvas)
+            .unwrap();
+
+        assert_snapshot("embedded_graphics", &canvas);
+    }
+}
@@ -0,0 +1,687 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A bitmap font, rendered by [`super::Display::draw_text`] on every backend.
+///
+/// Glyphs come from a BDF font, or are rasterized from a TrueType/OpenType font at load time.
+/// Characters missing from the font are looked up in its fallback fonts, in order.
+#[derive(Clone)]
+pub(crate) struct LedFont {
+    atlas: Arc<GlyphAtlas>,
+    fallbacks: Vec<Arc<LedFont>>,
+    ascent: i32,
+    descent: i32,
+    /// Width & height of the box covering every glyph
+    bounds: (u32, u32),
+    /// The font's `DEFAULT_CHAR`, shown for missing characters
+    default_char: Option<char>,
+}
+
+/// How the outlines of a scalable font are turned into LED pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Rasterization {
+    /// Pixels covered at least this much, from 0.0 to 1.0, are lit and all others stay off
+    Threshold(f32),
+    /// Edge pixels are blended with the background by how much of them is covered
+    Antialiased,
+}
+
+impl Default for Rasterization {
+    fn default() -> Self {
+        Self::Threshold(0.5)
+    }
+}
+
+/// Where a glyph is stored in the atlas, and how it is placed relative to the pen
+struct AtlasEntry {
+    left: i32,
+    top: i32,
+    width: u32,
+    height: u32,
+    advance: i32,
+    /// Index of the first word of the glyph's top row
+    start: usize,
+    /// Index of the opacity of the glyph's top-left pixel, for anti-aliased glyphs
+    alpha: Option<usize>,
+}
+
+/// Every glyph of a font rasterized into rows of bitmask words.
+///
+/// Rows of all glyphs share a single buffer, so drawing a glyph reads a few consecutive
+/// words instead of walking the font's bitmap pixel by pixel. Anti-aliased glyphs also
+/// keep the opacity of every pixel.
+#[derive(Default)]
+struct GlyphAtlas {
+    rows: Vec<u32>,
+    alpha: Vec<u8>,
+    glyphs: HashMap<char, AtlasEntry>,
+}
+
+impl GlyphAtlas {
+    /// Adds the glyph for `c`, with `coverage` giving the opacity of every pixel.
+    ///
+    /// Pixels with an opacity of 0 are not drawn. The opacity of the others is only kept
+    /// when `antialiased` is set, otherwise they are drawn opaque.
+    fn insert(
+        &mut self,
+        c: char,
+        mut entry: AtlasEntry,
+        antialiased: bool,
+        coverage: impl Fn(u32, u32) -> u8,
+    ) {
+        let stride = GlyphBitmap::stride(entry.width);
+        entry.start = self.rows.len();
+        self.rows
+            .resize(entry.start + stride * entry.height as usize, 0);
+        entry.alpha = antialiased.then_some(self.alpha.len());
+
+        for y in 0..entry.height {
+            for x in 0..entry.width {
+                let alpha = coverage(x, y);
+                if alpha > 0 {
+                    let index = entry.start + y as usize * stride + x as usize / 32;
+                    self.rows[index] |= 1 << (31 - x % 32);
+                }
+                if antialiased {
+                    self.alpha.push(alpha);
+                }
+            }
+        }
+        self.glyphs.insert(c, entry);
+    }
+}
+
+/// The glyphs drawn for one grapheme, see [`LedFont::cluster`].
+pub(crate) struct Cluster<'a> {
+    /// Glyph of the base character, `None` when nothing is drawn
+    pub(crate) base: Option<GlyphBitmap<'a>>,
+    /// Combining marks with their offset from the pen, empty without allocating for most
+    /// clusters
+    pub(crate) marks: Vec<(i32, GlyphBitmap<'a>)>,
+    /// Distance from the pen to the next cluster
+    pub(crate) advance: i32,
+}
+
+impl<'a> Cluster<'a> {
+    fn single(glyph: GlyphBitmap<'a>) -> Self {
+        Self {
+            base: Some(glyph),
+            marks: Vec::new(),
+            advance: glyph.advance,
+        }
+    }
+
+    /// Glyphs with their offset from the pen, the base character first.
+    pub(crate) fn glyphs(&self) -> impl Iterator<Item = (i32, GlyphBitmap<'a>)> + '_ {
+        self.base
+            .map(|base| (0, base))
+            .into_iter()
+            .chain(self.marks.iter().copied())
+    }
+}
+
+/// A glyph from the atlas, ready to be blitted with [`super::Display::draw_bitmap`].
+#[derive(Clone, Copy)]
+pub(crate) struct GlyphBitmap<'a> {
+    /// Offset from the pen to the left edge of the bitmap
+    pub(crate) left: i32,
+    /// Offset from the baseline to the top edge of the bitmap, negative above it
+    pub(crate) top: i32,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    /// Distance from the pen to the origin of the next glyph
+    pub(crate) advance: i32,
+    rows: &'a [u32],
+    alpha: Option<&'a [u8]>,
+}
+
+impl<'a> GlyphBitmap<'a> {
+    /// Words per row, each holding 32 pixels
+    fn stride(width: u32) -> usize {
+        width.div_ceil(32) as usize
+    }
+
+    /// The pixels of row `y`, the most significant bit of the first word is the leftmost.
+    fn row(&self, y: u32) -> &'a [u32] {
+        let stride = Self::stride(self.width);
+        &self.rows[y as usize * stride..(y as usize + 1) * stride]
+    }
+
+    /// Columns of the pixels drawn in row `y`, from left to right.
+    pub(crate) fn columns(&self, y: u32) -> impl Iterator<Item = u32> + 'a {
+        self.row(y)
+            .iter()
+            .enumerate()
+            .flat_map(|(word_index, &word)| {
+                let mut word = word;
+                std::iter::from_fn(move || {
+                    if word == 0 {
+                        return None;
+                    }
+                    let bit = word.leading_zeros();
+                    word &= !(1 << (31 - bit));
+                    Some(word_index as u32 * 32 + bit)
+                })
+            })
+    }
+
+    /// Opacity of the pixel at `(x, y)`, 255 for every drawn pixel of a glyph without
+    /// anti-aliasing.
+    pub(crate) fn alpha(&self, x: u32, y: u32) -> u8 {
+        match self.alpha {
+            Some(alpha) => alpha[(y * self.width + x) as usize],
+            None => 255,
+        }
+    }
+}
+
+impl LedFont {
+    pub fn new(bdf_file: &Path) -> Result<Self, &'static str> {
+        let contents = std::fs::read(bdf_file).map_err(|_| "Failed to open BDF font file")?;
+        Self::from_bytes(&contents)
+    }
+
+    /// Loads a TrueType or OpenType font, rasterizing its glyphs `size` pixels high.
+    pub(crate) fn new_scalable(
+        font_file: &Path,
+        size: f32,
+        rasterization: Rasterization,
+    ) -> Result<Self, &'static str> {
+        let contents =
+            std::fs::read(font_file).map_err(|_| "Failed to open TrueType/OpenType font file")?;
+        Self::from_scalable_bytes(&contents, size, rasterization)
+    }
+
+    /// Parses the contents of a BDF font file.
+    pub(crate) fn from_bytes(contents: &[u8]) -> Result<Self, &'static str> {
+        let font = read_bdf(contents)?;
+
+        let mut atlas = GlyphAtlas::default();
+        for (&c, glyph) in font.glyphs() {
+            // The bounding box offset is from the pen to the bottom-left corner of the bitmap
+            let bounds = glyph.bounds();
+            let entry = AtlasEntry {
+                left: bounds.x,
+                top: -(glyph.height() as i32) - bounds.y,
+                width: glyph.width(),
+                height: glyph.height(),
+                advance: advance(glyph),
+                start: 0,
+                alpha: None,
+            };
+            atlas.insert(
+                c,
+                entry,
+                false,
+                |x, y| if glyph.get(x, y) { 255 } else { 0 },
+            );
+        }
+
+        let bounds = font.bounds();
+        let property = |name: &str| match font.properties().get(name) {
+            Some(bdf::Property::Integer(value)) => Some(*value),
+            _ => None,
+        };
+        Ok(Self {
+            atlas: Arc::new(atlas),
+            fallbacks: Vec::new(),
+            ascent: property("FONT_ASCENT")
+                .map_or(bounds.height as i32 + bounds.y, |ascent| ascent as i32),
+            descent: property("FONT_DESCENT").map_or(-bounds.y, |descent| descent as i32),
+            bounds: (bounds.width, bounds.height),
+            default_char: property("DEFAULT_CHAR")
+                .and_then(|codepoint| u32::try_from(codepoint).ok())
+                .and_then(char::from_u32),
+        })
+    }
+
+    /// Parses a TrueType or OpenType font, rasterizing its glyphs `size` pixels high.
+    ///
+    /// Like `otf2bdf -r 72 -p <size>`, the size is the font's em height in pixels.
+    pub(crate) fn from_scalable_bytes(
+        contents: &[u8],
+        size: f32,
+        rasterization: Rasterization,
+    ) -> Result<Self, &'static str> {
+        if size.is_nan() || size <= 0.0 {
+            return Err("Font size must be positive");
+        }
+        let font = FontRef::try_from_slice(contents)
+            .map_err(|_| "Failed to parse TrueType/OpenType font file")?;
+        let scale = font.units_per_em().map_or(PxScale::from(size), |units| {
+            PxScale::from(size * font.height_unscaled() / units)
+        });
+        let scaled = font.as_scaled(scale);
+
+        let mut atlas = GlyphAtlas::default();
+        let mut width = 0;
+        for (id, c) in font.codepoint_ids() {
+            let advance = scaled.h_advance(id).round() as i32;
+            width = width.max(advance);
+
+            let Some(outline) = scaled.outline_glyph(id.with_scale(scale)) else {
+                // Glyphs without an outline, like the space, only move the pen
+                let entry = AtlasEntry {
+                    left: 0,
+                    top: 0,
+                    width: 0,
+                    height: 0,
+                    advance,
+                    start: 0,
+                    alpha: None,
+                };
+                atlas.insert(c, entry, false, |_, _| 0);
+                continue;
+            };
+
+            // Pixel bounds are relative to the pen on the baseline, with y pointing down
+            let bounds = outline.px_bounds();
+            let (glyph_width, glyph_height) = (bounds.width() as u32, bounds.height() as u32);
+            let mut coverage = vec![0.0; (glyph_width * glyph_height) as usize];
+            outline.draw(|x, y, covered| {
+                if x < glyph_width && y < glyph_height {
+                    coverage[(y * glyph_width + x) as usize] = covered;
+                }
+            });
+
+            let entry = AtlasEntry {
+                left: bounds.min.x as i32,
+                top: bounds.min.y as i32,
+                width: glyph_width,
+                height: glyph_height,
+                advance,
+                start: 0,
+                alpha: None,
+            };
+            let covered = |x: u32, y: u32| coverage[(y * glyph_width + x) as usize];
+            match rasterization {
+                Rasterization::Threshold(threshold) => {
+                    let threshold = threshold.clamp(f32::EPSILON, 1.0);
+                    atlas.insert(c, entry, false, |x, y| {
+                        if covered(x, y) >= threshold {
+                            255
+                        } else {
+                            0
+                        }
+                    });
+                }
+                Rasterization::Antialiased => {
+                    atlas.insert(c, entry, true, |x, y| {
+                        (covered(x, y).clamp(0.0, 1.0) * 255.0).round() as u8
+                    });
+                }
+            }
+        }
+
+        let ascent = scaled.ascent().round() as i32;
+        let descent = -scaled.descent().round() as i32;
+        Ok(Self {
+            atlas: Arc::new(atlas),
+            fallbacks: Vec::new(),
+            ascent,
+            descent,
+            bounds: (width.max(0) as u32, (ascent + descent).max(0) as u32),
+            default_char: None,
+        })
+    }
+
+    /// This font with `fallbacks` searched for the characters it is missing, in order.
+    pub(crate) fn with_fallbacks(&self, fallbacks: Vec<Arc<LedFont>>) -> Self {
+        Self {
+            fallbacks,
+            ..self.clone()
+        }
+    }
+
+    /// The glyph drawn for `c`.
+    ///
+    /// Characters missing from the font and its fallbacks are replaced by U+FFFD, the
+    /// font's `DEFAULT_CHAR` or `?`, in that order. `None` if none of them exist.
+    pub(crate) fn glyph(&self, c: char) -> Option<GlyphBitmap<'_>> {
+        [
+            Some(c),
+            Some(char::REPLACEMENT_CHARACTER),
+            self.default_char,
+            Some('?'),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|c| self.find(c))
+    }
+
+    /// The glyph for `c` from this font or the first fallback having one.
+    fn find(&self, c: char) -> Option<GlyphBitmap<'_>> {
+        let Some(entry) = self.atlas.glyphs.get(&c) else {
+            return self.fallbacks.iter().find_map(|font| font.find(c));
+        };
+        let length = GlyphBitmap::stride(entry.width) * entry.height as usize;
+        let pixels = (entry.width * entry.height) as usize;
+        Some(GlyphBitmap {
+            left: entry.left,
+            top: entry.top,
+            width: entry.width,
+            height: entry.height,
+            advance: entry.advance,
+            rows: &self.atlas.rows[entry.start..entry.start + length],
+            alpha: entry
+                .alpha
+                .map(|start| &self.atlas.alpha[start..start + pixels]),
+        })
+    }
+
+    /// Whether the font or one of its fallbacks has a glyph for `c`, without replacing it.
+    pub(crate) fn contains(&self, c: char) -> bool {
+        self.find(c).is_some()
+    }
+
+    /// The glyphs drawn for a grapheme cluster, like a letter followed by its accents.
+    ///
+    /// A cluster the font has a precomposed glyph for, like `é` for `e` and U+0301, is drawn
+    /// with that glyph. Otherwise the base character is drawn with its combining marks on
+    /// top, which also covers precomposed characters missing from the font.
+    pub(crate) fn cluster(&self, grapheme: &str) -> Cluster<'_> {
+        // Most graphemes are a single character the font has, which needs no normalization
+        let mut chars = grapheme.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            if let Some(glyph) = self.find(c) {
+                return Cluster::single(glyph);
+            }
+        }
+
+        let mut composed = grapheme.nfc();
+        if let (Some(c), None) = (composed.next(), composed.next()) {
+            if let Some(glyph) = self.find(c) {
+                return Cluster::single(glyph);
+            }
+        }
+
+        let mut chars = grapheme.nfd();
+        let Some(base) = chars.next().and_then(|c| self.glyph(c)) else {
+            return Cluster {
+                base: None,
+                marks: Vec::new(),
+                advance: 0,
+            };
+        };
+        // Marks end where the base ends, so they cover it whether they have an advance or not.
+        // Marks missing from the font are left out rather than replaced.
+        let marks = chars
+            .filter_map(|mark| self.find(mark))
+            .map(|mark| (base.advance - mark.advance, mark))
+            .collect();
+        Cluster {
+            base: Some(base),
+            marks,
+            advance: base.advance,
+        }
+    }
+
+    /// The clusters of `text`, one per grapheme.
+    pub(crate) fn clusters<'a>(&'a self, text: &'a str) -> impl Iterator<Item = Cluster<'a>> {
+        text.graphemes(true).map(|grapheme| self.cluster(grapheme))
+    }
+
+    /// Number of glyphs in the font.
+    pub(crate) fn glyph_count(&self) -> usize {
+        self.atlas.glyphs.len()
+    }
+
+    /// Width & height of the box covering every glyph, the height is the line pitch.
+    pub(crate) fn bounds(&self) -> (u32, u32) {
+        self.bounds
+    }
+
+    /// Pixels the font reaches above the baseline, its `FONT_ASCENT`.
+    pub(crate) fn ascent(&self) -> i32 {
+        self.ascent
+    }
+
+    /// Pixels the font reaches below the baseline, its `FONT_DESCENT`.
+    pub(crate) fn descent(&self) -> i32 {
+        self.descent
+    }
+
+    /// Width & height of `text` drawn on a single line.
+    ///
+    /// The width is the sum of the advances, the height the font's ascent plus descent.
+    pub(crate) fn measure(&self, text: &str) -> (i32, i32) {
+        let width = self.clusters(text).map(|cluster| cluster.advance).sum();
+        (width, self.ascent() + self.descent())
+    }
+
+    /// Splits `text` into lines no wider than `max_width`, breaking between words.
+    ///
+    /// Line breaks in `text` are kept, words wider than `max_width` are broken between
+    /// graphemes.
+    // Lays out Display::draw_text_box, which no widget uses yet
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub(crate) fn wrap(&self, text: &str, max_width: i32) -> Vec<String> {
+        if text.is_empty() {
+            return Vec::new();
+        }
+
+        let mut lines = Vec::new();
+        for paragraph in text.split('\n') {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                let joined = if line.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{} {}", line, word)
+                };
+                if self.measure(&joined).0 <= max_width {
+                    line = joined;
+                    continue;
+                }
+
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                for grapheme in word.graphemes(true) {
+                    // A line keeps at least one grapheme, even if it is too wide
+                    let joined = format!("{}{}", line, grapheme);
+                    if self.measure(&joined).0 > max_width && !line.is_empty() {
+                        lines.push(std::mem::replace(&mut line, grapheme.to_string()));
+                    } else {
+                        line = joined;
+                    }
+                }
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// Shortens `line` to fit `max_width` including an ellipsis at its end.
+    // Shortens the last line of Display::draw_text_box, which no widget uses yet
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub(crate) fn ellipsize(&self, line: &str, max_width: i32) -> String {
+        let ellipsis = if self.contains('…') { "…" } else { "..." };
+
+        let mut line = line.trim_end().to_string();
+        while !line.is_empty() && self.measure(&format!("{}{}", line, ellipsis)).0 > max_width {
+            let last = line
+                .grapheme_indices(true)
+                .next_back()
+                .map_or(0, |(index, _)| index);
+            line.truncate(last);
+            line.truncate(line.trim_end().len());
+        }
+        line + ellipsis
+    }
+}
+
+/// Parses a BDF font file.
+fn read_bdf(contents: &[u8]) -> Result<bdf::Font, &'static str> {
+    // The bdf crate expects comments to be quoted and panics on empty ones.
+    // Comments carry no font data, so they are dropped before parsing.
+    let contents = contents
+        .split(|&byte| byte == b'\n')
+        .filter(|line| !line.starts_with(b"COMMENT"))
+        .collect::<Vec<_>>()
+        .join(&b'\n');
+
+    bdf::read(contents.as_slice()).map_err(|_| "Failed to parse BDF font file")
+}
+
+/// Distance from the origin of `glyph` to the origin of the next one, its `DWIDTH`.
+fn advance(glyph: &bdf::Glyph) -> i32 {
+    glyph
+        .device_width()
+        .map_or(glyph.width() as i32, |&(x, _)| x as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{read_bdf, LedFont, Rasterization};
+    use crate::simulator::snapshot::{font, fonts_dir};
+
+    #[test]
+    fn measure_sums_the_advances() {
+        let font = font("5x8.bdf");
+
+        assert_eq!((font.ascent(), font.descent()), (7, 1));
+        assert_eq!(font.measure("3°C"), (15, 8));
+        assert_eq!(font.measure("-12.5°C"), (35, 8));
+        assert_eq!(font.measure(""), (0, 8));
+        // Missing characters take the space of the replacement glyph
+        assert_eq!(font.measure("\u{E000}"), font.measure("\u{FFFD}"));
+    }
+
+    #[test]
+    fn atlas_matches_the_bdf_bitmaps() {
+        for name in ["5x8.bdf", "9x18B.bdf", "tom-thumb.bdf"] {
+            let font = font(name);
+            let bdf = read_bdf(&std::fs::read(fonts_dir().join(name)).unwrap()).unwrap();
+            for (&c, bdf_glyph) in bdf.glyphs() {
+                let glyph = font.glyph(c).unwrap();
+                let bounds = bdf_glyph.bounds();
+                assert_eq!((glyph.width, glyph.height), (bounds.width, bounds.height));
+
+                let mut drawn = (0..glyph.height)
+                    .flat_map(|y| glyph.columns(y).map(move |x| (x, y)))
+                    .collect::<Vec<_>>();
+                drawn.sort_by_key(|&(x, y)| (y, x));
+                let expected = (0..bounds.height)
+                    .flat_map(|y| (0..bounds.width).map(move |x| (x, y)))
+                    .filter(|&(x, y)| bdf_glyph.get(x, y))
+                    .collect::<Vec<_>>();
+                assert_eq!(drawn, expected, "{:?} in {}", c, name);
+            }
+        }
+    }
+
+    #[test]
+    fn scalable_fonts_are_rasterized_at_the_pixel_size() {
+        let path = fonts_dir().join("DejaVuSansMono.ttf");
+        let mono = LedFont::new_scalable(&path, 12.0, Rasterization::default()).unwrap();
+        let smooth = LedFont::new_scalable(&path, 12.0, Rasterization::Antialiased).unwrap();
+
+        assert_eq!((mono.ascent(), mono.descent()), (11, 3));
+        assert_eq!(mono.measure("12:45"), (35, 14));
+        // Capitals end on the baseline
+        let glyph = mono.glyph('H').unwrap();
+        assert_eq!(glyph.top + glyph.height as i32, 0);
+
+        let alpha = |font: &LedFont| {
+            let glyph = font.glyph('O').unwrap();
+            (0..glyph.height)
+                .flat_map(|y| glyph.columns(y).map(move |x| glyph.alpha(x, y)))
+                .collect::<Vec<_>>()
+        };
+        assert!(alpha(&mono).iter().all(|&alpha| alpha == 255));
+        assert!(alpha(&smooth).iter().any(|&alpha| alpha < 255));
+        // Anti-aliasing also keeps the pixels below the threshold
+        assert!(alpha(&smooth).len() > alpha(&mono).len());
+    }
+
+    #[test]
+    fn fallback_fonts_fill_in_missing_glyphs() {
+        let primary = font("tom-thumb.bdf");
+        let font = primary.with_fallbacks(vec![Arc::new(font("5x8.bdf"))]);
+
+        assert!(!primary.contains('Ж'));
+        assert!(font.contains('Ж'));
+        // tom-thumb glyphs are 4 pixels apart, 5x8 glyphs 5
+        assert_eq!(font.measure("AЖ"), (9, 6));
+        // Characters missing from every font are still replaced
+        assert_eq!(font.measure("\u{E000}"), font.measure("?"));
+    }
+
+    #[test]
+    fn single_characters_are_clustered_without_allocating() {
+        let font = font("5x8.bdf");
+
+        for grapheme in ["A", "é", "\u{E000}"] {
+            let cluster = font.cluster(grapheme);
+            assert!(cluster.base.is_some(), "{:?}", grapheme);
+            assert_eq!(cluster.marks.capacity(), 0, "{:?}", grapheme);
+        }
+    }
+
+    #[test]
+    fn combining_marks_stay_on_their_letter() {
+        let font = font("5x8.bdf");
+        let glyphs = |text: &str| {
+            font.clusters(text)
+                .map(|cluster| cluster.glyphs().count())
+                .collect::<Vec<_>>()
+        };
+
+        // `e` and U+0301 compose to the font's `é`
+        assert_eq!(glyphs("e\u{301}"), [1]);
+        assert_eq!(font.measure("e\u{301}"), font.measure("é"));
+        // There is no `x` with an acute, so the mark is drawn over the `x`
+        assert_eq!(glyphs("x\u{301}y"), [2, 1]);
+        assert_eq!(font.measure("x\u{301}y"), (10, 8));
+        // `ǘ` is missing, so it is drawn as `u` with U+0308 and U+0301
+        assert!(!font.atlas.glyphs.contains_key(&'ǘ'));
+        assert_eq!(glyphs("ǘ"), [3]);
+        // Marks are never split from their letter
+        assert_eq!(font.ellipsize("cafe\u{301} au lait", 25), "cafe\u{301}…");
+    }
+
+    #[test]
+    fn wrap_breaks_between_words() {
+        let font = font("5x8.bdf");
+
+        // 6 characters of 5 pixels fit 32 pixels
+        assert_eq!(
+            font.wrap("Partly cloudy with a chance\nof rain", 32),
+            ["Partly", "cloudy", "with a", "chance", "of", "rain"]
+        );
+        assert_eq!(
+            font.wrap("Thunderstorm  hail", 32),
+            ["Thunde", "rstorm", "hail"]
+        );
+        assert!(font.wrap("", 32).is_empty());
+    }
+
+    #[test]
+    fn ellipsize_fits_the_ellipsis() {
+        let font = font("5x8.bdf");
+
+        assert_eq!(font.ellipsize("chance of", 32), "chanc…");
+        assert_eq!(font.ellipsize("with a", 32), "with…");
+        assert_eq!(font.ellipsize("with", 32), "with…");
+    }
+
+    #[test]
+    fn empty_comments_are_skipped() {
+        // clR6x12 has bare `COMMENT` lines, which the bdf crate cannot parse
+        let path = fonts_dir().join("clR6x12.bdf");
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .any(|line| line.trim_end() == "COMMENT"));
+
+        let font = LedFont::new(&path).unwrap();
+
+        assert!(font.contains('A'));
+    }
+}
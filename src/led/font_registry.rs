@@ -0,0 +1,284 @@
+//! Fonts loaded by name from a directory, parsed once and shared.
+
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use super::{font::Rasterization, LedFont};
+
+/// Fonts built into the binary, used when their file does not exist
+const EMBEDDED_FONTS: [(&str, &[u8]); 2] = [
+    ("6x12", include_bytes!("../../fonts/6x12.bdf")),
+    ("5x8", include_bytes!("../../fonts/5x8.bdf")),
+];
+
+/// Loads fonts by name and caches them, so every font is only parsed once.
+///
+/// TrueType and OpenType fonts are rasterized at the pixel size following their name, like
+/// `DejaVuSansMono.ttf:12`. Adding `:aa` draws them anti-aliased, `DejaVuSansMono.ttf:12:aa`.
+pub(crate) struct FontRegistry {
+    dir: PathBuf,
+    /// Fonts searched for the characters a font is missing, in order
+    fallbacks: Vec<String>,
+    /// Fonts as parsed from their file
+    fonts: HashMap<String, Arc<LedFont>>,
+    /// Fonts with their fallbacks, as returned by [`FontRegistry::get`]
+    chains: HashMap<String, Arc<LedFont>>,
+}
+
+impl FontRegistry {
+    /// Registry loading fonts from `dir`.
+    pub(crate) fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            fallbacks: Vec::new(),
+            fonts: HashMap::new(),
+            chains: HashMap::new(),
+        }
+    }
+
+    /// Searches the fonts named `fallbacks` for characters missing from the loaded fonts.
+    pub(crate) fn with_fallbacks(mut self, fallbacks: Vec<String>) -> Self {
+        self.fallbacks = fallbacks;
+        self
+    }
+
+    /// The file a font is loaded from.
+    ///
+    /// Names are looked up in the font directory, `.bdf` is added when they have no
+    /// extension. Names with a directory, like `fonts/6x12.bdf`, are used as a path.
+    pub(crate) fn path(&self, name: &str) -> PathBuf {
+        let (name, _) = split_size(name);
+        let path = Path::new(name);
+        if path.components().count() > 1 {
+            return path.to_path_buf();
+        }
+
+        let path = self.dir.join(name);
+        if path.extension().is_none() {
+            path.with_extension("bdf")
+        } else {
+            path
+        }
+    }
+
+    /// Loads a font by name or path, see [`FontRegistry::path`], followed by the fallbacks.
+    ///
+    /// When the file of a font given by name does not exist, an embedded font with the same
+    /// name is used instead. Fonts given by path are never replaced.
+    ///
+    /// # Errors
+    /// If there is neither a file nor an embedded font for the font or one of its fallbacks,
+    /// or a file cannot be read or parsed.
+    pub(crate) fn get(&mut self, name: &str) -> Result<Arc<LedFont>> {
+        if let Some(font) = self.chains.get(name) {
+            return Ok(font.clone());
+        }
+
+        let font = self.load(name)?;
+        let mut fallbacks = Vec::new();
+        for fallback in self.fallbacks.clone() {
+            if fallback != name {
+                let font = self
+                    .load(&fallback)
+                    .with_context(|| format!("Failed to load fallback font {:?}", fallback))?;
+                fallbacks.push(font);
+            }
+        }
+
+        let font = if fallbacks.is_empty() {
+            font
+        } else {
+            Arc::new(font.with_fallbacks(fallbacks))
+        };
+        self.chains.insert(name.to_string(), font.clone());
+        Ok(font)
+    }
+
+    /// Loads a font without its fallbacks.
+    fn load(&mut self, name: &str) -> Result<Arc<LedFont>> {
+        if let Some(font) = self.fonts.get(name) {
+            return Ok(font.clone());
+        }
+
+        let path = self.path(name);
+        let size = match (is_scalable(&path), split_size(name)) {
+            (true, (_, Some(size))) => Some(size),
+            (true, (file, None)) => {
+                bail!("Font {:?} needs a pixel size, like \"{}:12\"", name, file)
+            }
+            (false, (_, Some(_))) => bail!("Font {:?} is a bitmap font without sizes", name),
+            (false, (_, None)) => None,
+        };
+
+        let font = match std::fs::read(&path) {
+            Ok(contents) => match size {
+                Some((size, rasterization)) => {
+                    LedFont::from_scalable_bytes(&contents, size, rasterization)
+                }
+                None => LedFont::from_bytes(&contents),
+            }
+            .map_err(|e| anyhow!("{} {}", e, path.display()))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => match embedded(name, size.is_some()) {
+                Some(contents) => {
+                    LedFont::from_bytes(contents).expect("Embedded fonts are valid BDF files")
+                }
+                None => bail!(
+                    "Font {:?} not found, {} does not exist",
+                    name,
+                    path.display()
+                ),
+            },
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to open font file {}", path.display()))
+            }
+        };
+
+        let font = Arc::new(font);
+        self.fonts.insert(name.to_string(), font.clone());
+        Ok(font)
+    }
+}
+
+/// Splits a name like `DejaVuSansMono.ttf:12:aa` into the file and the size to rasterize at.
+fn split_size(name: &str) -> (&str, Option<(f32, Rasterization)>) {
+    let (rest, rasterization) = match name.strip_suffix(":aa") {
+        Some(rest) => (rest, Rasterization::Antialiased),
+        None => (name, Rasterization::default()),
+    };
+    match rest.rsplit_once(':') {
+        Some((file, size)) => match size.parse() {
+            Ok(size) => (file, Some((size, rasterization))),
+            Err(_) => (name, None),
+        },
+        None => (name, None),
+    }
+}
+
+/// Whether `path` is a TrueType or OpenType font, by its extension.
+pub(crate) fn is_scalable(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        ["ttf", "otf"]
+            .iter()
+            .any(|scalable| extension.eq_ignore_ascii_case(scalable))
+    })
+}
+
+/// The embedded font for a font name, e.g. `6x12` for `6x12` or `6x12.bdf`.
+///
+/// Names with a directory are paths, which are not replaced by an embedded font, so a typo
+/// in a path is reported instead of silently showing another font. Only bitmap fonts are
+/// embedded, so there is none for a `scalable` font.
+fn embedded(name: &str, scalable: bool) -> Option<&'static [u8]> {
+    let path = Path::new(name);
+    if scalable || path.components().count() > 1 {
+        return None;
+    }
+    let name = path.file_stem()?;
+    EMBEDDED_FONTS
+        .iter()
+        .find(|(embedded, _)| name == *embedded)
+        .map(|(_, contents)| *contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::FontRegistry;
+    use crate::simulator::snapshot::fonts_dir;
+
+    #[test]
+    fn fonts_are_loaded_by_name_and_cached() {
+        let mut fonts = FontRegistry::new(fonts_dir());
+
+        let font = fonts.get("tom-thumb").unwrap();
+
+        assert_eq!(fonts.path("tom-thumb"), fonts_dir().join("tom-thumb.bdf"));
+        assert_eq!(font.ascent(), 5);
+        assert!(Arc::ptr_eq(&font, &fonts.get("tom-thumb").unwrap()));
+    }
+
+    #[test]
+    fn embedded_fonts_are_used_when_the_file_is_missing() {
+        let mut fonts = FontRegistry::new("missing-fonts");
+
+        assert_eq!(fonts.get("6x12").unwrap().measure("14:35"), (30, 12));
+        assert_eq!(fonts.get("5x8.bdf").unwrap().measure("Fri"), (15, 8));
+        // A path is not a font name, so there is no embedded font for it
+        assert!(fonts.get("missing-fonts/5x8.bdf").is_err());
+    }
+
+    fn error(fonts: &mut FontRegistry, name: &str) -> String {
+        match fonts.get(name) {
+            Ok(_) => panic!("{} loaded", name),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn missing_fonts_name_the_file() {
+        let mut fonts = FontRegistry::new("missing-fonts");
+
+        let error = error(&mut fonts, "7x13");
+
+        assert!(error.contains("\"7x13\""), "{}", error);
+        assert!(error.contains("7x13.bdf does not exist"), "{}", error);
+    }
+
+    #[test]
+    fn font_paths_are_only_loaded_from_disk() {
+        let mut fonts = FontRegistry::new("missing-fonts");
+        let path = fonts_dir().join("5x8.bdf");
+
+        assert_eq!(
+            fonts.get(path.to_str().unwrap()).unwrap().measure("Fri"),
+            (15, 8)
+        );
+        let error = error(&mut fonts, "/opt/fonts/5x8.bdf");
+
+        assert!(
+            error.contains("/opt/fonts/5x8.bdf does not exist"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn fonts_are_chained_with_the_fallbacks() {
+        let mut fonts = FontRegistry::new(fonts_dir()).with_fallbacks(vec!["5x8".to_string()]);
+
+        let font = fonts.get("tom-thumb").unwrap();
+
+        assert!(font.contains('Ж'));
+        assert!(Arc::ptr_eq(&font, &fonts.get("tom-thumb").unwrap()));
+        // A fallback is not chained to itself
+        assert_eq!(fonts.get("5x8").unwrap().measure("Ж"), (5, 8));
+
+        let mut fonts = FontRegistry::new(fonts_dir()).with_fallbacks(vec!["7x99".to_string()]);
+        assert!(error(&mut fonts, "tom-thumb").contains("fallback font \"7x99\""));
+    }
+
+    #[test]
+    fn scalable_fonts_are_loaded_at_their_size() {
+        let mut fonts = FontRegistry::new(fonts_dir());
+
+        let font = fonts.get("DejaVuSansMono.ttf:12").unwrap();
+        let antialiased = fonts.get("DejaVuSansMono.ttf:12:aa").unwrap();
+
+        assert_eq!(font.measure("12:45"), (35, 14));
+        assert!(!Arc::ptr_eq(&font, &antialiased));
+        assert_eq!(
+            fonts.path("DejaVuSansMono.ttf:12:aa"),
+            fonts_dir().join("DejaVuSansMono.ttf")
+        );
+        assert!(error(&mut fonts, "DejaVuSansMono.ttf").contains("needs a pixel size"));
+        assert!(error(&mut fonts, "6x12:12").contains("bitmap font"));
+    }
+}
@@ -0,0 +1,75 @@
+//! Paces the draw loop at a fixed frame rate, for animations like scrolling text.
+
+use std::time::{Duration, Instant};
+
+/// Schedules frames at a fixed interval and measures the time since the first one.
+///
+/// The next frame is due one interval after the previous one was due, not after it was
+/// drawn, so drawing time does not slow the animations down. A frame that is late starts
+/// right away and the schedule continues from there instead of rushing to catch up.
+pub(crate) struct FrameClock {
+    start: Instant,
+    interval: Duration,
+    /// When the next frame is due
+    next: Instant,
+}
+
+impl FrameClock {
+    pub(crate) fn new(interval: Duration) -> Self {
+        let start = Instant::now();
+        Self {
+            start,
+            interval,
+            next: start,
+        }
+    }
+
+    /// Time since the clock started, which animations are timed from.
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Waits until the next frame is due.
+    pub(crate) fn tick(&mut self) {
+        self.next += self.interval;
+        let now = Instant::now();
+        match self.next.checked_duration_since(now) {
+            Some(wait) => std::thread::sleep(wait),
+            None => self.next = now,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::FrameClock;
+
+    const INTERVAL: Duration = Duration::from_millis(10);
+
+    #[test]
+    fn frames_are_an_interval_apart() {
+        let mut clock = FrameClock::new(INTERVAL);
+
+        for _ in 0..3 {
+            clock.tick();
+        }
+
+        assert!(clock.elapsed() >= 3 * INTERVAL);
+    }
+
+    #[test]
+    fn late_frames_are_not_rushed() {
+        let mut clock = FrameClock::new(INTERVAL);
+
+        // Drawing took three frames, so the next one starts right away...
+        std::thread::sleep(3 * INTERVAL);
+        clock.tick();
+        // ...and the one after waits a full interval
+        let start = Instant::now();
+        clock.tick();
+
+        assert!(start.elapsed() >= INTERVAL);
+    }
+}
@@ -0,0 +1,283 @@
+//! Offscreen layers, merged into a frame by a [`Compositor`].
+//!
+//! Widgets draw on a [`Layer`] like on any other [`Display`], so overlapping elements can be
+//! stacked with transparency instead of overwriting each other.
+
+use super::{color::LedRgba, Display, LedColor};
+
+/// How the colors of a layer are combined with the pixels below it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum BlendMode {
+    /// The layer covers what is below
+    #[default]
+    Normal,
+    /// Channels are added up, brightening what is below
+    // The clock & weather layers do not overlap, so there is nothing to brighten
+    #[cfg_attr(not(test), allow(dead_code))]
+    Add,
+    /// Channels are multiplied, darkening what is below
+    // Nothing is darkened either, e.g. to dim a widget behind an alert
+    #[cfg_attr(not(test), allow(dead_code))]
+    Multiply,
+}
+
+impl BlendMode {
+    /// The color shown where an opaque pixel `above` lies on `below`.
+    fn apply(self, below: LedColor, above: LedColor) -> LedColor {
+        let channel = |below: u8, above: u8| match self {
+            Self::Normal => above,
+            Self::Add => below.saturating_add(above),
+            Self::Multiply => ((below as u32 * above as u32 + 127) / 255) as u8,
+        };
+        LedColor {
+            red: channel(below.red, above.red),
+            green: channel(below.green, above.green),
+            blue: channel(below.blue, above.blue),
+        }
+    }
+}
+
+/// An RGBA buffer covering a region of the frame, transparent until drawn on.
+///
+/// Drawing uses coordinates relative to the top-left corner of the layer, which is placed
+/// at `(x, y)` in the frame.
+pub(crate) struct Layer {
+    pub(crate) x: i32,
+    pub(crate) y: i32,
+    width: u32,
+    height: u32,
+    pixels: Vec<LedRgba>,
+    /// Opacity of the whole layer, from 0.0 to 1.0
+    pub(crate) opacity: f32,
+    pub(crate) blend_mode: BlendMode,
+}
+
+impl Layer {
+    pub(crate) fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            pixels: vec![LedRgba::transparent(); (width * height) as usize],
+            opacity: 1.0,
+            blend_mode: BlendMode::Normal,
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || x >= self.width as i32 || y < 0 || y >= self.height as i32 {
+            return None;
+        }
+        Some((y * self.width as i32 + x) as usize)
+    }
+
+    /// Retrieves the color & opacity at the given coordinate, `None` outside the layer.
+    pub(crate) fn get_rgba(&self, x: i32, y: i32) -> Option<LedRgba> {
+        self.index(x, y).map(|index| self.pixels[index])
+    }
+
+    /// Replaces the color & opacity at the given coordinate.
+    pub(crate) fn set_rgba(&mut self, x: i32, y: i32, color: LedRgba) {
+        if let Some(index) = self.index(x, y) {
+            self.pixels[index] = color;
+        }
+    }
+
+    /// Merges the layer onto `target`, e.g. a frame or another layer.
+    ///
+    /// A [`Compositor`] does this for each of its layers. Blending goes through
+    /// [`Display::blend`], so translucent pixels stay translucent on a layer below.
+    pub(crate) fn composite<D: Display + ?Sized>(&self, target: &mut D) {
+        let opacity = self.opacity.clamp(0.0, 1.0);
+        if opacity <= 0.0 {
+            return;
+        }
+
+        for (index, pixel) in self.pixels.iter().enumerate() {
+            if pixel.alpha == 0 {
+                continue;
+            }
+            let x = self.x + (index % self.width as usize) as i32;
+            let y = self.y + (index / self.width as usize) as i32;
+            let Some(below) = target.get(x, y) else {
+                continue;
+            };
+
+            let color = self.blend_mode.apply(below, pixel.color());
+            let alpha = pixel.alpha as f32 / 255.0 * opacity;
+            target.blend(x, y, &color, alpha);
+        }
+    }
+}
+
+impl Display for Layer {
+    fn canvas_size(&self) -> (i32, i32) {
+        (self.width as i32, self.height as i32)
+    }
+
+    /// Sets an opaque pixel.
+    fn set(&mut self, x: i32, y: i32, color: &LedColor) {
+        self.set_rgba(x, y, (*color).into());
+    }
+
+    /// Retrieves the color without its opacity, transparent pixels are black.
+    fn get(&self, x: i32, y: i32) -> Option<LedColor> {
+        self.get_rgba(x, y).map(LedRgba::color)
+    }
+
+    /// Composites `color` over the pixel, so anti-aliased edges stay translucent.
+    fn blend(&mut self, x: i32, y: i32, color: &LedColor, alpha: f32) {
+        if alpha <= 0.0 {
+            return;
+        }
+        if let Some(below) = self.get_rgba(x, y) {
+            let above = color.with_alpha((alpha.min(1.0) * 255.0).round() as u8);
+            self.set_rgba(x, y, above.over(below));
+        }
+    }
+
+    /// Makes the whole layer transparent.
+    fn clear(&mut self) {
+        self.pixels.fill(LedRgba::transparent());
+    }
+
+    fn fill(&mut self, color: &LedColor) {
+        self.pixels.fill((*color).into());
+    }
+
+    fn swap(&mut self) {
+        // Layers are shown through `Compositor::compose`
+    }
+}
+
+/// Stack of layers merged bottom to top into a frame.
+#[derive(Default)]
+pub(crate) struct Compositor {
+    layers: Vec<Layer>,
+}
+
+impl Compositor {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a layer on top of all others, returning its index.
+    pub(crate) fn push(&mut self, layer: Layer) -> usize {
+        self.layers.push(layer);
+        self.layers.len() - 1
+    }
+
+    /// The layer with the given index, for drawing on it.
+    ///
+    /// # Panics
+    /// If there is no layer with this index.
+    pub(crate) fn layer_mut(&mut self, index: usize) -> &mut Layer {
+        &mut self.layers[index]
+    }
+
+    /// Merges every layer onto `target`, in the order they were pushed.
+    ///
+    /// What `target` already shows is the background, clear it first to start from black.
+    pub(crate) fn compose<D: Display + ?Sized>(&self, target: &mut D) {
+        for layer in &self.layers {
+            layer.composite(target);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlendMode, Compositor, Layer};
+    use crate::{
+        led::{color::LedRgba, Display},
+        led_color,
+        simulator::led_canvas::LedCanvas,
+    };
+
+    fn pixel(canvas: &LedCanvas, x: i32, y: i32) -> u32 {
+        canvas.get(x, y).unwrap().into()
+    }
+
+    #[test]
+    fn layers_are_placed_in_their_region() {
+        let mut compositor = Compositor::new();
+        let layer = compositor.push(Layer::new(2, 1, 2, 2));
+        compositor.layer_mut(layer).fill(&led_color!("#FF0000"));
+        // Outside the layer, so never shown
+        compositor
+            .layer_mut(layer)
+            .set(2, 0, &led_color!("#00FF00"));
+
+        let mut canvas = LedCanvas::new(4, 4);
+        compositor.compose(&mut canvas);
+
+        let buffer = canvas.pixel_buffer.lock().unwrap();
+        let red = buffer.iter().filter(|&&pixel| pixel == 0xFF0000).count();
+        assert_eq!(red, 4);
+        assert!(!buffer.contains(&0x00FF00));
+        drop(buffer);
+        assert_eq!(pixel(&canvas, 2, 1), 0xFF0000);
+        assert_eq!(pixel(&canvas, 3, 2), 0xFF0000);
+    }
+
+    #[test]
+    fn layers_are_composited_bottom_to_top() {
+        let mut compositor = Compositor::new();
+        let background = compositor.push(Layer::new(0, 0, 4, 1));
+        compositor
+            .layer_mut(background)
+            .fill(&led_color!("#204080"));
+
+        let mut half = Layer::new(0, 0, 1, 1);
+        half.fill(&led_color!("#FFFFFF"));
+        half.opacity = 0.5;
+        compositor.push(half);
+
+        let mut add = Layer::new(1, 0, 1, 1);
+        add.fill(&led_color!("#F01010"));
+        add.blend_mode = BlendMode::Add;
+        compositor.push(add);
+
+        let mut multiply = Layer::new(2, 0, 1, 1);
+        multiply.fill(&led_color!("#808080"));
+        multiply.blend_mode = BlendMode::Multiply;
+        compositor.push(multiply);
+
+        let mut canvas = LedCanvas::new(1, 4);
+        compositor.compose(&mut canvas);
+
+        assert_eq!(pixel(&canvas, 0, 0), 0x90A0C0);
+        assert_eq!(pixel(&canvas, 1, 0), 0xFF5090);
+        assert_eq!(pixel(&canvas, 2, 0), 0x102040);
+        assert_eq!(pixel(&canvas, 3, 0), 0x204080);
+    }
+
+    #[test]
+    fn antialiasing_on_a_layer_stays_translucent() {
+        let mut layer = Layer::new(0, 0, 4, 4);
+
+        layer.blend(0, 0, &led_color!("#FFFFFF"), 0.5);
+        layer.blend(0, 0, &led_color!("#FFFFFF"), 0.5);
+        layer.blend(1, 1, &led_color!("#FF0000"), 0.25);
+
+        assert_eq!(
+            layer.get_rgba(0, 0),
+            Some(led_color!("#FFFFFF").with_alpha(192))
+        );
+        assert_eq!(layer.get_rgba(2, 2), Some(LedRgba::transparent()));
+        assert_eq!(
+            layer.get_rgba(1, 1),
+            Some(led_color!("#FF0000").with_alpha(64))
+        );
+
+        let mut canvas = LedCanvas::new(4, 4);
+        canvas.fill(&led_color!("#0000FF"));
+        let mut compositor = Compositor::new();
+        compositor.push(layer);
+        compositor.compose(&mut canvas);
+
+        assert_eq!(pixel(&canvas, 1, 1), 0x4000BF);
+        assert_eq!(pixel(&canvas, 2, 2), 0x0000FF);
+    }
+}
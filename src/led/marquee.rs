@@ -0,0 +1,234 @@
+//! Text scrolling horizontally through a box, for strings wider than the panel.
+
+use std::time::Duration;
+
+use super::{layer::Layer, Display, HorizontalAlign, LedFont, TextPaint, VerticalAlign};
+
+/// How a marquee continues once the end of its text came into view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum MarqueeMode {
+    /// Keeps scrolling, with the start of the text following its end
+    #[default]
+    Loop,
+    /// Scrolls back to the start
+    // The date & weather lines loop, so their start follows right after their end
+    #[cfg_attr(not(test), allow(dead_code))]
+    PingPong,
+}
+
+/// Text scrolling through the box with its top-left corner at `(x, y)`.
+///
+/// The scroll position follows the time since the text was set instead of counting
+/// frames, so the speed does not depend on the refresh rate. Text fitting the box is
+/// shown without scrolling.
+pub(crate) struct Marquee {
+    text: String,
+    /// Scroll speed in pixels per second
+    pub(crate) speed: f64,
+    /// How long the start of the text is shown before scrolling, and its end in ping-pong mode
+    pub(crate) pause: Duration,
+    pub(crate) mode: MarqueeMode,
+    /// Pixels between the end of the text and its start while looping
+    pub(crate) gap: u32,
+    /// Covers the box, so everything outside it is cut off. Kept to be redrawn every frame.
+    layer: Layer,
+    /// When the current text was set
+    started: Duration,
+}
+
+impl Marquee {
+    pub(crate) fn new(text: impl Into<String>, x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            text: text.into(),
+            speed: 16.0,
+            pause: Duration::from_secs(1),
+            mode: MarqueeMode::Loop,
+            gap: 16,
+            layer: Layer::new(x, y, width, height),
+            started: Duration::ZERO,
+        }
+    }
+
+    /// Shows `text` from now on, `elapsed` being the time the animations are timed from.
+    ///
+    /// A different text scrolls from its start again, the same text keeps its position.
+    pub(crate) fn set_text(&mut self, text: &str, elapsed: Duration) {
+        if self.text != text {
+            self.text = text.to_string();
+            self.started = elapsed;
+        }
+    }
+
+    /// Pixels the text is scrolled to the left at `elapsed`.
+    pub(crate) fn offset(&self, font: &LedFont, elapsed: Duration) -> i32 {
+        let (text_width, _) = font.measure(&self.text);
+        let (width, _) = self.layer.canvas_size();
+        let overflow = text_width - width;
+        if overflow <= 0 || self.speed <= 0.0 {
+            return 0;
+        }
+
+        let pause = self.pause.as_secs_f64();
+        let elapsed = elapsed.saturating_sub(self.started).as_secs_f64();
+        let offset = match self.mode {
+            MarqueeMode::Loop => {
+                let distance = (text_width + self.gap as i32) as f64;
+                let elapsed = elapsed % (pause + distance / self.speed);
+                (elapsed - pause).max(0.0) * self.speed
+            }
+            MarqueeMode::PingPong => {
+                let distance = overflow as f64;
+                let scroll = distance / self.speed;
+                let elapsed = elapsed % (2.0 * (pause + scroll));
+                if elapsed < pause {
+                    0.0
+                } else if elapsed < pause + scroll {
+                    (elapsed - pause) * self.speed
+                } else if elapsed < 2.0 * pause + scroll {
+                    distance
+                } else {
+                    distance - (elapsed - 2.0 * pause - scroll) * self.speed
+                }
+            }
+        };
+        offset.floor() as i32
+    }
+
+    /// Draws the text at its position at `elapsed`, clipped to the box.
+    pub(crate) fn draw<D: Display + ?Sized>(
+        &mut self,
+        canvas: &mut D,
+        font: &LedFont,
+        paint: &TextPaint,
+        elapsed: Duration,
+    ) {
+        let offset = self.offset(font, elapsed);
+        let (text_width, _) = font.measure(&self.text);
+        let (_, height) = self.layer.canvas_size();
+
+        self.layer.clear();
+        let mut draw_at = |x: i32| {
+            self.layer.draw_text_aligned(
+                font,
+                &self.text,
+                x,
+                0,
+                text_width.max(0) as u32,
+                height as u32,
+                HorizontalAlign::Left,
+                VerticalAlign::Middle,
+                paint,
+            );
+        };
+        draw_at(-offset);
+        if self.mode == MarqueeMode::Loop && offset > 0 {
+            draw_at(text_width + self.gap as i32 - offset);
+        }
+
+        self.layer.composite(canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Marquee, MarqueeMode};
+    use crate::{
+        led::LedFont,
+        led_color,
+        simulator::{led_canvas::LedCanvas, snapshot::font},
+    };
+
+    // 5x8 characters are 5 pixels wide, so this is 95 pixels
+    const TEXT: &str = "Light drizzle, 12°C";
+
+    fn marquee(mode: MarqueeMode) -> Marquee {
+        let mut marquee = Marquee::new(TEXT, 8, 4, 40, 8);
+        marquee.speed = 20.0;
+        marquee.pause = Duration::from_secs(1);
+        marquee.gap = 20;
+        marquee.mode = mode;
+        marquee
+    }
+
+    fn at(marquee: &Marquee, font: &LedFont, seconds: f64) -> i32 {
+        marquee.offset(font, Duration::from_secs_f64(seconds))
+    }
+
+    #[test]
+    fn loop_pauses_at_the_start_of_every_round() {
+        let (font, marquee) = (font("5x8.bdf"), marquee(MarqueeMode::Loop));
+
+        assert_eq!(font.measure(TEXT).0, 95);
+        assert_eq!(at(&marquee, &font, 0.9), 0);
+        assert_eq!(at(&marquee, &font, 1.5), 10);
+        // One round scrolls the text and the gap, 115 pixels, in 5.75 seconds
+        assert_eq!(at(&marquee, &font, 6.725), 114);
+        assert_eq!(at(&marquee, &font, 6.8), 0);
+        assert_eq!(at(&marquee, &font, 7.75), 0);
+        assert_eq!(at(&marquee, &font, 8.25), 10);
+    }
+
+    #[test]
+    fn ping_pong_scrolls_back() {
+        let (font, marquee) = (font("5x8.bdf"), marquee(MarqueeMode::PingPong));
+
+        // The last 55 pixels scroll into view in 2.75 seconds
+        assert_eq!(at(&marquee, &font, 0.5), 0);
+        assert_eq!(at(&marquee, &font, 2.0), 20);
+        assert_eq!(at(&marquee, &font, 4.0), 55);
+        assert_eq!(at(&marquee, &font, 5.25), 45);
+        assert_eq!(at(&marquee, &font, 7.6), 0);
+        assert_eq!(at(&marquee, &font, 8.0), 0);
+    }
+
+    #[test]
+    fn short_text_does_not_scroll() {
+        let font = font("5x8.bdf");
+        let mut marquee = marquee(MarqueeMode::Loop);
+        marquee.set_text("12°C", Duration::ZERO);
+
+        assert_eq!(at(&marquee, &font, 0.0), 0);
+        assert_eq!(at(&marquee, &font, 3.0), 0);
+    }
+
+    #[test]
+    fn new_text_scrolls_from_the_start() {
+        let font = font("5x8.bdf");
+        let mut marquee = marquee(MarqueeMode::Loop);
+
+        // The same text keeps scrolling
+        marquee.set_text(TEXT, Duration::from_secs(1));
+        assert_eq!(at(&marquee, &font, 1.5), 10);
+
+        marquee.set_text("Heavy rain, 9°C and windy", Duration::from_secs(2));
+        assert_eq!(at(&marquee, &font, 2.5), 0);
+        assert_eq!(at(&marquee, &font, 3.5), 10);
+    }
+
+    #[test]
+    fn text_is_clipped_to_the_box() {
+        let font = font("5x8.bdf");
+        let mut marquee = marquee(MarqueeMode::Loop);
+
+        for seconds in [0.0, 2.0, 6.5] {
+            let mut canvas = LedCanvas::new(16, 64);
+            marquee.draw(
+                &mut canvas,
+                &font,
+                &led_color!("#FFFFFF").into(),
+                Duration::from_secs_f64(seconds),
+            );
+
+            let pixels = canvas.pixel_buffer.lock().unwrap();
+            let lit = (0..16 * 64).filter(|&index| pixels[index] != 0);
+            let (inside, outside): (Vec<_>, Vec<_>) = lit.partition(|index| {
+                let (x, y) = (index % 64, index / 64);
+                (8..48).contains(&x) && (4..12).contains(&y)
+            });
+            assert!(!inside.is_empty());
+            assert!(outside.is_empty(), "{:?} at {}s", outside, seconds);
+        }
+    }
+}
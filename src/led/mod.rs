@@ -1,3 +1,22 @@
+pub(crate) mod color;
+pub(crate) mod display;
+pub(crate) mod draw_target;
+pub(crate) mod font;
+pub(crate) mod font_registry;
+pub(crate) mod frame_clock;
+pub(crate) mod layer;
+pub(crate) mod marquee;
+pub(crate) mod paint;
+#[cfg(feature = "hardware")]
+pub(crate) mod rpi;
+#[cfg(not(feature = "hardware"))]
+pub(crate) mod simulator;
+
+pub(crate) use color::LedColor;
+pub(crate) use display::{Display, HorizontalAlign, TextOverflow, VerticalAlign};
+pub(crate) use font::LedFont;
+pub(crate) use font_registry::FontRegistry;
+pub(crate) use paint::TextPaint;
 
 #[macro_export]
 macro_rules! led_color {
@@ -7,14 +26,12 @@ macro_rules! led_color {
         } else {
             $hex
         };
-        let color_as_u32 = u32::from_str_radix(hex, 16)
-            .expect("Could not parse hex value to u32");
+        let color_as_u32 = u32::from_str_radix(hex, 16).expect("Could not parse hex value to u32");
 
-        LedColor {
+        $crate::led::LedColor {
             red: ((color_as_u32 >> 16) & 0xFF) as u8,
             green: ((color_as_u32 >> 8) & 0xFF) as u8,
-            blue: (color_as_u32 & 0xFF) as u8
+            blue: (color_as_u32 & 0xFF) as u8,
         }
     }};
-
 }
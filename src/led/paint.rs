@@ -0,0 +1,217 @@
+//! Colors & effects of text, see [`TextPaint`].
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use super::LedColor;
+
+/// How the pixels of text are colored
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub(crate) enum Fill {
+    /// A single color
+    Solid(LedColor),
+    /// From the first color at the left end of the text to the second at its right end
+    HorizontalGradient(LedColor, LedColor),
+    /// From the first color at the top of the line to the second at its bottom
+    VerticalGradient(LedColor, LedColor),
+    /// Cycles through all hues every `width` pixels, moving `speed` pixels per second
+    Rainbow { width: u32, speed: f32 },
+}
+
+/// A one pixel border drawn behind text, in a second color
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Edge {
+    /// Surrounds the glyphs on every side
+    Outline(LedColor),
+    /// Offset one pixel down and to the right
+    Shadow(LedColor),
+}
+
+impl Edge {
+    /// The color of the edge and where it is drawn relative to the glyphs.
+    pub(crate) fn offsets(self) -> (LedColor, &'static [(i32, i32)]) {
+        match self {
+            Self::Outline(color) => (
+                color,
+                &[
+                    (-1, -1),
+                    (0, -1),
+                    (1, -1),
+                    (-1, 0),
+                    (1, 0),
+                    (-1, 1),
+                    (0, 1),
+                    (1, 1),
+                ],
+            ),
+            Self::Shadow(color) => (color, &[(1, 1)]),
+        }
+    }
+}
+
+/// Colors & effects of text, passed to [`super::Display::draw_text`].
+///
+/// A plain [`LedColor`] converts into a paint filling the text with that color.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TextPaint {
+    pub(crate) fill: Fill,
+    pub(crate) edge: Option<Edge>,
+    /// Text is shown for the first half of every period and hidden for the second
+    pub(crate) blink: Option<Duration>,
+    /// Time since the effects started, which moves rainbows and blinks the text
+    pub(crate) elapsed: Duration,
+}
+
+impl TextPaint {
+    /// Whether the text is shown, or hidden by blinking.
+    pub(crate) fn visible(&self) -> bool {
+        match self.blink {
+            Some(period) if !period.is_zero() => {
+                self.elapsed.as_nanos() % period.as_nanos() < period.as_nanos() / 2
+            }
+            _ => true,
+        }
+    }
+}
+
+impl From<LedColor> for TextPaint {
+    fn from(color: LedColor) -> Self {
+        Self {
+            fill: Fill::Solid(color),
+            edge: None,
+            blink: None,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+/// A fill spread over the box covered by the text it colors.
+pub(crate) struct Brush {
+    fill: Fill,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    /// Seconds since the effects started
+    elapsed: f32,
+}
+
+impl Brush {
+    /// `fill` spread over the box with its top-left corner at `(x, y)`.
+    pub(crate) fn new(
+        fill: Fill,
+        (x, y): (i32, i32),
+        (width, height): (i32, i32),
+        elapsed: Duration,
+    ) -> Self {
+        Self {
+            fill,
+            x,
+            y,
+            width,
+            height,
+            elapsed: elapsed.as_secs_f32(),
+        }
+    }
+
+    /// A single color, wherever it is used.
+    pub(crate) fn solid(color: LedColor) -> Self {
+        Self::new(Fill::Solid(color), (0, 0), (0, 0), Duration::ZERO)
+    }
+
+    /// The color of the pixel at `(x, y)`.
+    pub(crate) fn color(&self, x: i32, y: i32) -> LedColor {
+        // From 0.0 at the start of the box to 1.0 at its end
+        let along = |position: i32, start: i32, length: i32| {
+            if length <= 1 {
+                0.0
+            } else {
+                (position - start) as f32 / (length - 1) as f32
+            }
+        };
+
+        match self.fill {
+            Fill::Solid(color) => color,
+            Fill::HorizontalGradient(from, to) => from.blend(to, along(x, self.x, self.width)),
+            Fill::VerticalGradient(from, to) => from.blend(to, along(y, self.y, self.height)),
+            Fill::Rainbow { width, speed } => {
+                let offset = (x - self.x) as f32 - self.elapsed * speed;
+                LedColor::from_hue(offset / width.max(1) as f32)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Brush, Fill, TextPaint};
+    use crate::led_color;
+
+    fn color(brush: &Brush, x: i32, y: i32) -> u32 {
+        brush.color(x, y).into()
+    }
+
+    #[test]
+    fn fills_are_spread_over_the_box() {
+        let (red, blue) = (led_color!("#FF0000"), led_color!("#0000FF"));
+        let horizontal = Brush::new(
+            Fill::HorizontalGradient(red, blue),
+            (10, 0),
+            (11, 8),
+            Duration::ZERO,
+        );
+        let vertical = Brush::new(
+            Fill::VerticalGradient(red, blue),
+            (10, 0),
+            (11, 8),
+            Duration::ZERO,
+        );
+
+        assert_eq!(color(&horizontal, 10, 5), 0xFF0000);
+        assert_eq!(color(&horizontal, 15, 5), 0x800080);
+        assert_eq!(color(&horizontal, 20, 0), 0x0000FF);
+        assert_eq!(color(&vertical, 20, 0), 0xFF0000);
+        assert_eq!(color(&vertical, 10, 7), 0x0000FF);
+    }
+
+    #[test]
+    fn rainbows_move_over_time() {
+        let rainbow = |seconds| {
+            Brush::new(
+                Fill::Rainbow {
+                    width: 30,
+                    speed: 10.0,
+                },
+                (0, 0),
+                (64, 8),
+                Duration::from_secs_f32(seconds),
+            )
+        };
+
+        assert_eq!(color(&rainbow(0.0), 0, 0), 0xFF0000);
+        assert_eq!(color(&rainbow(0.0), 10, 0), 0x00FF00);
+        assert_eq!(color(&rainbow(0.0), 20, 0), 0x0000FF);
+        assert_eq!(color(&rainbow(0.0), 30, 0), 0xFF0000);
+        // After a second, every hue moved 10 pixels to the right
+        assert_eq!(color(&rainbow(1.0), 10, 0), 0xFF0000);
+        assert_eq!(color(&rainbow(1.0), 0, 0), 0x0000FF);
+    }
+
+    #[test]
+    fn blinking_text_is_hidden_for_half_the_period() {
+        let mut paint = TextPaint::from(led_color!("#FFFFFF"));
+        assert!(paint.visible());
+
+        paint.blink = Some(Duration::from_secs(1));
+        let visible = [0.0, 0.25, 0.5, 0.75, 1.0, 1.4].map(|seconds| {
+            paint.elapsed = Duration::from_secs_f32(seconds);
+            paint.visible()
+        });
+        assert_eq!(visible, [true, true, false, false, true, true]);
+    }
+}
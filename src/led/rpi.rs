@@ -0,0 +1,92 @@
+use rpi_led_matrix::{LedCanvas, LedMatrix};
+
+use super::{Display, LedColor};
+
+impl From<LedColor> for rpi_led_matrix::LedColor {
+    fn from(color: LedColor) -> Self {
+        Self {
+            red: color.red,
+            green: color.green,
+            blue: color.blue,
+        }
+    }
+}
+
+/// [`Display`] adapter for a physical panel.
+///
+/// Drawing goes straight to an offscreen canvas which is handed to the matrix on
+/// [`Display::swap`]. rpi-led-matrix cannot read pixels back, which blending needs, so the
+/// colors of both canvases are kept alongside and swapped with them.
+pub(crate) struct RpiDisplay {
+    matrix: LedMatrix,
+    canvas: Option<LedCanvas>,
+    width: i32,
+    height: i32,
+    /// Colors of the offscreen canvas
+    pixels: Vec<u32>,
+    /// Colors of the canvas on display, the offscreen canvas after the next swap
+    shown: Vec<u32>,
+}
+
+impl RpiDisplay {
+    pub(crate) fn new(matrix: LedMatrix) -> Self {
+        let canvas = matrix.offscreen_canvas();
+        let (width, height) = canvas.canvas_size();
+        let size = (width * height) as usize;
+        Self {
+            matrix,
+            canvas: Some(canvas),
+            width,
+            height,
+            pixels: vec![0; size],
+            shown: vec![0; size],
+        }
+    }
+
+    fn canvas_mut(&mut self) -> &mut LedCanvas {
+        self.canvas
+            .as_mut()
+            .expect("Canvas is only taken during swap")
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        ((0..self.width).contains(&x) && (0..self.height).contains(&y))
+            .then(|| (y * self.width + x) as usize)
+    }
+}
+
+impl Display for RpiDisplay {
+    fn canvas_size(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    fn set(&mut self, x: i32, y: i32, color: &LedColor) {
+        if let Some(index) = self.index(x, y) {
+            self.pixels[index] = (*color).into();
+            self.canvas_mut().set(x, y, &(*color).into());
+        }
+    }
+
+    fn get(&self, x: i32, y: i32) -> Option<LedColor> {
+        self.index(x, y).map(|index| self.pixels[index].into())
+    }
+
+    fn clear(&mut self) {
+        self.pixels.fill(0);
+        self.canvas_mut().clear();
+    }
+
+    fn fill(&mut self, color: &LedColor) {
+        self.pixels.fill((*color).into());
+        self.canvas_mut().fill(&(*color).into());
+    }
+
+    fn swap(&mut self) {
+        let canvas = self
+            .canvas
+            .take()
+            .expect("Canvas is only taken during swap");
+        self.canvas = Some(self.matrix.swap(canvas));
+        std::mem::swap(&mut self.pixels, &mut self.shown);
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::{Arc, Mutex};
+
+use crate::simulator::{led_canvas::LedCanvas, LedMatrix};
+
+use super::{font::GlyphBitmap, paint::Brush, Display, LedColor, TextOverflow};
+
+/// [`Display`] adapter for the simulator.
+///
+/// Drawing goes to an offscreen canvas which is handed to the matrix on [`Display::swap`],
+/// the same way frames are presented on a physical panel.
+pub(crate) struct SimulatorDisplay {
+    matrix: LedMatrix,
+    canvas: Option<LedCanvas>,
+}
+
+impl SimulatorDisplay {
+    pub(crate) fn new(matrix: LedMatrix) -> Self {
+        let canvas = Some(matrix.offscreen_canvas());
+        Self { matrix, canvas }
+    }
+
+    /// The buffer holding the frame currently on display
+    pub(crate) fn pixel_buffer(&self) -> Arc<Mutex<Vec<u32>>> {
+        self.matrix.pixel_buffer.clone()
+    }
+
+    fn canvas(&self) -> &LedCanvas {
+        self.canvas
+            .as_ref()
+            .expect("Canvas is only taken during swap")
+    }
+
+    fn canvas_mut(&mut self) -> &mut LedCanvas {
+        self.canvas
+            .as_mut()
+            .expect("Canvas is only taken during swap")
+    }
+}
+
+impl Display for SimulatorDisplay {
+    fn canvas_size(&self) -> (i32, i32) {
+        self.canvas().canvas_size()
+    }
+
+    fn set(&mut self, x: i32, y: i32, color: &LedColor) {
+        self.canvas_mut().set(x, y, color);
+    }
+
+    fn get(&self, x: i32, y: i32) -> Option<LedColor> {
+        self.canvas().get(x, y)
+    }
+
+    fn clear(&mut self) {
+        self.canvas_mut().clear();
+    }
+
+    fn fill(&mut self, color: &LedColor) {
+        self.canvas_mut().fill(color);
+    }
+
+    fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: &LedColor) {
+        self.canvas_mut().draw_line(x0, y0, x1, y1, color);
+    }
+
+    fn draw_circle(&mut self, x: i32, y: i32, radius: u32, color: &LedColor) {
+        self.canvas_mut().draw_circle(x, y, radius, color);
+    }
+
+    fn draw_bitmap(
+        &mut self,
+        glyph: &GlyphBitmap,
+        x: i32,
+        y: i32,
+        brush: &Brush,
+        overflow: TextOverflow,
+    ) {
+        self.canvas_mut().draw_bitmap(glyph, x, y, brush, overflow);
+    }
+
+    fn swap(&mut self) {
+        let canvas = self
+            .canvas
+            .take()
+            .expect("Canvas is only taken during swap");
+        self.canvas = Some(self.matrix.swap(canvas));
+    }
+}
@@ -1,148 +1,397 @@
-#[cfg(target_os = "linux")]
-use rpi_led_matrix::{
-    LedCanvas, LedColor, LedFont, LedMatrix, LedMatrixOptions, LedRuntimeOptions,
-};
-use weather_api::api::WeatherApiResponse;
+#[cfg(all(feature = "simulator", feature = "hardware"))]
+compile_error!("The `simulator` and `hardware` features are mutually exclusive");
 
-#[cfg(any(target_os = "macos", target_os = "windows"))]
-mod simulator;
+#[cfg(feature = "hardware")]
+use led::rpi::RpiDisplay;
+#[cfg(feature = "hardware")]
+use rpi_led_matrix::{LedMatrix, LedMatrixOptions, LedRuntimeOptions};
 
-#[cfg(any(target_os = "macos", target_os = "windows"))]
-use crate::simulator::{
-    led_canvas::{LedCanvas, LedCanvasTrait, LedFont},
-    LedColor, LedMatrix, LedMatrixOptions, LedRuntimeOptions,
+#[cfg(not(feature = "hardware"))]
+use crate::simulator::{LedMatrix, LedMatrixOptions, LedRuntimeOptions};
+#[cfg(not(feature = "hardware"))]
+use led::simulator::SimulatorDisplay;
+
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use cli::{Cli, Command};
+use config::{Config, DisplayConfig};
+use led::{
+    frame_clock::FrameClock,
+    layer::{Compositor, Layer},
+    marquee::Marquee,
+    Display, FontRegistry, HorizontalAlign, LedColor, LedFont, TextPaint, VerticalAlign,
 };
+use weather_api::{api::WeatherApiResponse, canvas::WeatherWidget};
 
+mod cli;
+mod config;
 mod led;
+mod simulator;
 mod weather_api;
 
 use chrono::Timelike;
-use std::path::Path;
-
-const REFRESH_RATE_MS: u32 = 500;
-const MATRIX_HEIGHT: u32 = 64;
-const MATRIX_WIDTH: u32 = 64;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-fn main() {
-    use std::sync::{Arc, Mutex};
+/// The [`Display`] implementation selected by the backend feature
+#[cfg(feature = "hardware")]
+type Backend = RpiDisplay;
+#[cfg(not(feature = "hardware"))]
+type Backend = SimulatorDisplay;
 
+fn main() -> Result<()> {
     dotenv::dotenv().ok();
 
-    let canvas = setup();
+    let cli = Cli::parse();
+    let config = Config::load(cli.config.as_deref())?;
+
+    match cli.command.unwrap_or(Command::Run) {
+        Command::Run => run(config),
+        #[cfg(not(feature = "hardware"))]
+        Command::Simulate(args) => run(args.apply(config)),
+        Command::Snapshot(args) => cli::snapshot::run(&config, args),
+        Command::Fonts { command } => cli::fonts::run(&config, command),
+        Command::Weather { command } => cli::weather::run(command),
+    }
+}
+
+/// Drives the display until the process is stopped or the simulator window is closed.
+fn run(config: Config) -> Result<()> {
+    let refresh_rate = Duration::from_millis(config.display.refresh_rate_ms.get() as u64);
+
+    let mut fonts = FontRegistry::new(&config.display.font_dir)
+        .with_fallbacks(config.display.font_fallbacks.clone());
+    let font_lg = load_font(&mut fonts, "display.font_large", &config.display.font_large)?;
+    let font_sm = load_font(&mut fonts, "display.font_small", &config.display.font_small)?;
+
+    let canvas = setup(&config)?;
+    #[cfg(not(feature = "hardware"))]
+    let (width, height) = config.matrix.size();
+    #[cfg(feature = "simulator")]
+    let pixel_buffer = canvas.pixel_buffer();
     let weather_response = Arc::new(Mutex::new(WeatherApiResponse::default()));
 
-    start_draw_loop(canvas, weather_response.clone());
-    start_weather_api_polling(weather_response);
+    #[cfg(not(feature = "hardware"))]
+    if config.simulator.terminal {
+        simulator::terminal::start_terminal_polling(
+            canvas.pixel_buffer(),
+            width,
+            height,
+            refresh_rate,
+        );
+    }
 
-    // Offset the refresh polling for the window by half the rate
-    // to minimize screen glitches due to incomplete buffer writes
-    #[cfg(any(target_os = "macos", target_os = "windows"))]
-    std::thread::sleep(std::time::Duration::from_millis(REFRESH_RATE_MS as u64 / 2));
+    // Weather polling runs on its own thread and must be started before the draw loop,
+    // which blocks the main thread when there is no window to poll
+    start_weather_api_polling(
+        weather_response.clone(),
+        Duration::from_secs(config.weather.interval_secs.get() as u64),
+    );
+    start_draw_loop(
+        canvas,
+        font_lg,
+        font_sm,
+        config.display.clone(),
+        refresh_rate,
+        weather_response,
+    );
 
     // Polling should be invoked last as this runs on the main thread and is blocking
-    // (must be run on main thread because minifb cannot create a window from a thread)
-    #[cfg(any(target_os = "macos", target_os = "windows"))]
-    start_window_polling(std::time::Duration::from_millis(REFRESH_RATE_MS as u64));
+    // (must be run on main thread because minifb cannot create a window from a thread).
+    // Returning from main once the window is closed ends the draw and weather threads.
+    #[cfg(feature = "simulator")]
+    simulator::window::start_window_polling(pixel_buffer, width, height, config.simulator.scale);
+
+    Ok(())
+}
+
+/// Loads a font configured under `key`, naming the key when it fails.
+fn load_font(fonts: &mut FontRegistry, key: &str, name: &str) -> Result<Arc<LedFont>> {
+    fonts.get(name).map_err(|e| anyhow!("{}: {:#}", key, e))
 }
 
 fn start_draw_loop(
-    mut canvas: LedCanvas,
-    weather_response: std::sync::Arc<std::sync::Mutex<WeatherApiResponse>>,
+    mut canvas: Backend,
+    font_lg: Arc<LedFont>,
+    font_sm: Arc<LedFont>,
+    display: DisplayConfig,
+    refresh_rate: Duration,
+    weather_response: Arc<Mutex<WeatherApiResponse>>,
 ) {
-    let font_lg = LedFont::new(Path::new("fonts/6x12.bdf")).unwrap();
-    let font_sm = LedFont::new(Path::new("fonts/5x8.bdf")).unwrap();
-    let color = led_color!("#2EC866");
-
-    if cfg!(any(target_os = "macos", target_os = "windows")) {
-        #[cfg(any(target_os = "macos", target_os = "windows"))]
-        std::thread::spawn(move || {
-            draw_loop(&mut canvas, font_lg, font_sm, color, weather_response)
-        });
-    } else {
-        draw_loop(&mut canvas, font_lg, font_sm, color, weather_response)
-    }
+    // The window needs the main thread, so the simulator draws from a separate one.
+    // Hardware and the headless simulator draw on the main thread.
+    #[cfg(feature = "simulator")]
+    std::thread::spawn(move || {
+        draw_loop(
+            &mut canvas,
+            font_lg,
+            font_sm,
+            display,
+            refresh_rate,
+            weather_response,
+        )
+    });
+
+    #[cfg(not(feature = "simulator"))]
+    draw_loop(
+        &mut canvas,
+        font_lg,
+        font_sm,
+        display,
+        refresh_rate,
+        weather_response,
+    )
 }
 
-fn draw_loop(
-    mut canvas: &mut LedCanvas,
-    font_lg: LedFont,
-    font_sm: LedFont,
-    color: LedColor,
-    weather_response: std::sync::Arc<std::sync::Mutex<WeatherApiResponse>>,
+fn draw_loop<D: Display>(
+    canvas: &mut D,
+    font_lg: Arc<LedFont>,
+    font_sm: Arc<LedFont>,
+    display: DisplayConfig,
+    refresh_rate: Duration,
+    weather_response: Arc<Mutex<WeatherApiResponse>>,
 ) {
+    let (width, height) = canvas.canvas_size();
+    let mut dashboard = Dashboard::new(width as u32, height as u32, font_lg, font_sm);
+    let mut clock = FrameClock::new(refresh_rate);
     loop {
-        canvas.clear();
-        let now = chrono::Local::now();
+        let now = chrono::Local::now().naive_local();
+        let weather = weather_response.lock().unwrap();
+        dashboard.draw(canvas, &display, &weather, now, clock.elapsed());
+        drop(weather);
 
-        let time = now.format("%H:%M").to_string();
-        let date = now.format("%a %b %e").to_string();
+        canvas.swap();
 
-        canvas.draw_text(&font_lg, time.as_str(), 18, 0, &color, 0, false);
-        canvas.draw_text(&font_sm, date.as_str(), 2, 18, &color, 0, false);
+        clock.tick();
+    }
+}
 
-        if now.second() != 0 {
-            canvas.draw_line(2, 14, 2 + now.second() as i32, 14, &color);
+/// Height of the clock at the top of the frame, the weather fills the rest
+const CLOCK_HEIGHT: u32 = 32;
+/// Pixels kept free around the clock widgets
+const MARGIN: i32 = 2;
+
+/// The widgets of a frame, each drawn on its own layer.
+///
+/// Widgets & layers are kept between frames, so their buffers are allocated once and
+/// scrolling text keeps its position.
+pub(crate) struct Dashboard {
+    font_lg: Arc<LedFont>,
+    font_sm: Arc<LedFont>,
+    compositor: Compositor,
+    /// Layer of the clock
+    clock_layer: usize,
+    clock: ClockWidget,
+    /// Layer of the weather
+    weather_layer: usize,
+    weather: WeatherWidget,
+}
+
+impl Dashboard {
+    pub(crate) fn new(
+        width: u32,
+        height: u32,
+        font_lg: Arc<LedFont>,
+        font_sm: Arc<LedFont>,
+    ) -> Self {
+        let mut compositor = Compositor::new();
+        let clock_layer = compositor.push(Layer::new(0, 0, width, CLOCK_HEIGHT.min(height)));
+        let weather_layer = compositor.push(Layer::new(
+            0,
+            CLOCK_HEIGHT as i32,
+            width,
+            height.saturating_sub(CLOCK_HEIGHT),
+        ));
+        Self {
+            clock: ClockWidget::new(&font_lg, &font_sm, width),
+            weather: WeatherWidget::new(&font_sm, 1, 1, width.saturating_sub(2)),
+            font_lg,
+            font_sm,
+            compositor,
+            clock_layer,
+            weather_layer,
         }
+    }
 
-        let weather = weather_response.lock().unwrap();
-        weather_api::canvas::draw_weather(&mut canvas, &font_sm, &weather, 1, 33, &color);
+    /// Draws a complete frame showing the given time and weather.
+    ///
+    /// Scrolling text and text effects are timed from `elapsed`, the time since the first
+    /// frame, so they move with every frame rather than with the clock.
+    pub(crate) fn draw<D: Display>(
+        &mut self,
+        canvas: &mut D,
+        display: &DisplayConfig,
+        weather: &WeatherApiResponse,
+        now: chrono::NaiveDateTime,
+        elapsed: Duration,
+    ) {
+        let layer = self.compositor.layer_mut(self.clock_layer);
+        layer.clear();
+        let paint = display.clock_style.paint(display.color, elapsed);
+        self.clock.draw(
+            layer,
+            &self.font_lg,
+            &self.font_sm,
+            &display.color,
+            &paint,
+            now,
+            elapsed,
+        );
 
-        std::thread::sleep(std::time::Duration::from_millis(REFRESH_RATE_MS as u64));
+        let layer = self.compositor.layer_mut(self.weather_layer);
+        layer.clear();
+        let paint = display.weather_style.paint(display.color, elapsed);
+        self.weather
+            .draw(layer, &self.font_sm, weather, &paint, elapsed);
+
+        canvas.clear();
+        self.compositor.compose(canvas);
     }
 }
 
-#[cfg(any(target_os = "macos", target_os = "windows"))]
-fn start_window_polling(
-    refresh_rate: std::time::Duration,
-) {
-    let pixel_buffer = None; // @TODO
-    let mut window = minifb::Window::new(
-        "LED matrix",
-        MATRIX_WIDTH,
-        MATRIX_HEIGHT,
-        minifb::WindowOptions {
-            resize: false,
-            scale: minifb::Scale::X16,
-            ..minifb::WindowOptions::default()
-        },
-    )
-    .expect("Failed to create window.");
+/// The time with a bar counting the seconds below it, followed by the date.
+struct ClockWidget {
+    /// Row of the seconds bar
+    bar: i32,
+    /// The date, scrolling when wider than the panel
+    date: Marquee,
+}
 
-    loop {
-        window
-            .update_with_buffer(
-                &pixel_buffer.lock().unwrap(),
-                width as usize,
-                height as usize,
-            )
-            .unwrap();
-        std::thread::sleep(refresh_rate);
+impl ClockWidget {
+    /// Lays out the clock across a canvas `width` pixels wide.
+    fn new(font_lg: &LedFont, font_sm: &LedFont, width: u32) -> Self {
+        // The bar lies just below the time
+        let bar = font_lg.ascent() + font_lg.descent() + MARGIN;
+        let date = Marquee::new(
+            "",
+            MARGIN,
+            bar + 2 * MARGIN,
+            (width as i32 - 2 * MARGIN).max(0) as u32,
+            (font_sm.ascent() + font_sm.descent()) as u32,
+        );
+        Self { bar, date }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw<D: Display>(
+        &mut self,
+        canvas: &mut D,
+        font_lg: &LedFont,
+        font_sm: &LedFont,
+        color: &LedColor,
+        paint: &TextPaint,
+        now: chrono::NaiveDateTime,
+        elapsed: Duration,
+    ) {
+        let time = now.format("%H:%M").to_string();
+        let date = now.format("%a %b %e").to_string();
+
+        let (width, _) = canvas.canvas_size();
+        canvas.draw_text_aligned(
+            font_lg,
+            time.as_str(),
+            0,
+            0,
+            width as u32,
+            (self.bar - MARGIN) as u32,
+            HorizontalAlign::Center,
+            VerticalAlign::Top,
+            paint,
+        );
+
+        // The bar grows by a pixel each second
+        if now.second() != 0 {
+            let bar = self.bar;
+            canvas.draw_line(MARGIN, bar, MARGIN + now.second() as i32, bar, color);
+        }
+
+        self.date.set_text(&date, elapsed);
+        self.date.draw(canvas, font_sm, paint, elapsed);
     }
 }
 
-fn start_weather_api_polling(
-    weather_response: std::sync::Arc<std::sync::Mutex<WeatherApiResponse>>,
-) {
+fn start_weather_api_polling(weather_response: Arc<Mutex<WeatherApiResponse>>, interval: Duration) {
     std::thread::spawn(move || loop {
         let weather = weather_api::api::get_api_details().expect("Unable to fetch weather");
 
         println!("{:?}", weather);
 
         *weather_response.lock().unwrap() = weather;
-        std::thread::sleep(std::time::Duration::from_secs(300));
+        std::thread::sleep(interval);
     });
 }
 
-pub(crate) fn setup() -> LedCanvas {
+pub(crate) fn setup(config: &Config) -> Result<Backend> {
+    config.matrix.validate()?;
+
     let mut options = LedMatrixOptions::new();
-    options.set_cols(MATRIX_WIDTH);
-    options.set_rows(MATRIX_HEIGHT);
-    options.set_hardware_mapping("adafruit-hat");
+    options.set_cols(config.matrix.cols.get());
+    options.set_rows(config.matrix.rows.get());
+    options.set_hardware_mapping(&config.matrix.hardware_mapping);
+    options.set_chain_length(config.matrix.chain_length.get());
+    options.set_parallel(config.matrix.parallel.get());
+    options
+        .set_pwm_bits(config.matrix.pwm_bits)
+        .map_err(|e| anyhow!("matrix.pwm_bits: {}", e))?;
+    options
+        .set_brightness(config.matrix.brightness)
+        .map_err(|e| anyhow!("matrix.brightness: {}", e))?;
+    options.set_scan_mode(config.matrix.scan_mode);
+    options.set_led_rgb_sequence(&config.matrix.led_rgb_sequence);
+    options.set_pixel_mapper_config(&config.matrix.pixel_mapper_config);
+    options.set_row_addr_type(config.matrix.row_addr_type);
+    options.set_multiplexing(config.matrix.multiplexing);
+    options.set_inverse_colors(config.matrix.inverse_colors);
 
     let mut rt_options = LedRuntimeOptions::new();
-    rt_options.set_gpio_slowdown(4);
+    rt_options.set_gpio_slowdown(config.runtime.gpio_slowdown);
+    rt_options.set_drop_privileges(config.runtime.drop_privileges);
+
+    let matrix = LedMatrix::new(Some(options), Some(rt_options))
+        .map_err(anyhow::Error::msg)
+        .context("Failed to initialize the LED matrix")?;
+    Ok(Backend::new(matrix))
+}
 
-    LedMatrix::new(Some(options), Some(rt_options))
-        .unwrap()
-        .offscreen_canvas()
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::Dashboard;
+    use crate::{
+        config::DisplayConfig,
+        simulator::{
+            led_canvas::LedCanvas,
+            snapshot::{assert_snapshot, font},
+        },
+        weather_api::api::{WeatherApiResponse, MOCK_RESPONSE},
+    };
+
+    #[test]
+    fn draw_frame_matches_snapshot() {
+        let weather: WeatherApiResponse = serde_json::from_str(MOCK_RESPONSE).unwrap();
+        let now = chrono::NaiveDate::from_ymd_opt(2024, 3, 15)
+            .unwrap()
+            .and_hms_opt(14, 35, 42)
+            .unwrap();
+        let mut canvas = LedCanvas::new(64, 64);
+
+        let mut dashboard = Dashboard::new(
+            64,
+            64,
+            Arc::new(font("6x12.bdf")),
+            Arc::new(font("5x8.bdf")),
+        );
+        dashboard.draw(
+            &mut canvas,
+            &DisplayConfig::default(),
+            &weather,
+            now,
+            Duration::ZERO,
+        );
+
+        assert_snapshot("frame", &canvas);
+    }
 }
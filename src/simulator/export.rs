@@ -0,0 +1,157 @@
+#[cfg(feature = "simulator")]
+use std::time::{Duration, Instant};
+use std::{fs::File, io::BufWriter, path::Path};
+
+use anyhow::{Context, Result};
+
+use super::led_canvas::LedCanvas;
+
+// Delay of the last recorded frame, which has no successor to measure against
+#[cfg(feature = "simulator")]
+const LAST_FRAME_DELAY: Duration = Duration::from_millis(1000);
+
+/// How frames are rendered into exported images.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ExportStyle {
+    /// Width & height of a single LED in image pixels
+    pub(crate) scale: u32,
+    /// Renders every LED as a round dot on a black background instead of a filled square.
+    /// Only visible from a scale of 3 upwards.
+    pub(crate) led_dots: bool,
+}
+
+impl Default for ExportStyle {
+    fn default() -> Self {
+        Self {
+            scale: 1,
+            led_dots: false,
+        }
+    }
+}
+
+impl ExportStyle {
+    fn image_size(&self, width: u32, height: u32) -> (u32, u32) {
+        (width * self.scale.max(1), height * self.scale.max(1))
+    }
+
+    /// Converts a `0xRRGGBB` frame into upscaled RGB8 image data.
+    fn render(&self, frame: &[u32], width: u32, height: u32) -> Vec<u8> {
+        let scale = self.scale.max(1);
+        let (image_width, image_height) = self.image_size(width, height);
+        let mut rgb = vec![0u8; (image_width * image_height * 3) as usize];
+
+        // Dot radius relative to the LED center, leaving a small gap between neighbours
+        let radius = scale as f32 * 0.45;
+
+        for image_y in 0..image_height {
+            for image_x in 0..image_width {
+                if self.led_dots && scale >= 3 {
+                    let dx = (image_x % scale) as f32 + 0.5 - scale as f32 / 2.0;
+                    let dy = (image_y % scale) as f32 + 0.5 - scale as f32 / 2.0;
+                    if dx * dx + dy * dy > radius * radius {
+                        continue;
+                    }
+                }
+
+                let pixel = frame[((image_y / scale) * width + image_x / scale) as usize];
+                let index = ((image_y * image_width + image_x) * 3) as usize;
+                rgb[index] = (pixel >> 16) as u8;
+                rgb[index + 1] = (pixel >> 8) as u8;
+                rgb[index + 2] = pixel as u8;
+            }
+        }
+
+        rgb
+    }
+}
+
+impl LedCanvas {
+    /// Writes the current contents of the canvas as a PNG image.
+    pub(crate) fn save_png(&self, path: &Path, style: ExportStyle) -> Result<()> {
+        let (image_width, image_height) = style.image_size(self.width, self.height);
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+
+        let mut encoder = png::Encoder::new(BufWriter::new(file), image_width, image_height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let rgb = style.render(&self.pixel_buffer.lock().unwrap(), self.width, self.height);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&rgb)?;
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+/// Records frames into an animated GIF.
+///
+/// Every frame is shown until the next one was pushed, so the animation plays back
+/// with the same timing it was drawn with.
+#[cfg(feature = "simulator")]
+pub(crate) struct GifRecorder {
+    encoder: gif::Encoder<BufWriter<File>>,
+    width: u32,
+    height: u32,
+    style: ExportStyle,
+    pending: Option<(Vec<u8>, Instant)>,
+}
+
+#[cfg(feature = "simulator")]
+impl GifRecorder {
+    pub(crate) fn new(path: &Path, width: u32, height: u32, style: ExportStyle) -> Result<Self> {
+        let (image_width, image_height) = style.image_size(width, height);
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+
+        let mut encoder = gif::Encoder::new(
+            BufWriter::new(file),
+            image_width as u16,
+            image_height as u16,
+            &[],
+        )?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+
+        Ok(Self {
+            encoder,
+            width,
+            height,
+            style,
+            pending: None,
+        })
+    }
+
+    /// Adds the current contents of the canvas to the animation.
+    pub(crate) fn push_frame(&mut self, canvas: &LedCanvas) -> Result<()> {
+        let now = Instant::now();
+        if let Some((rgb, shown_at)) = self.pending.take() {
+            self.write_frame(&rgb, now - shown_at)?;
+        }
+        let rgb = self.style.render(
+            &canvas.pixel_buffer.lock().unwrap(),
+            self.width,
+            self.height,
+        );
+        self.pending = Some((rgb, now));
+        Ok(())
+    }
+
+    /// Writes the last frame and closes the file.
+    pub(crate) fn finish(mut self) -> Result<()> {
+        if let Some((rgb, _)) = self.pending.take() {
+            self.write_frame(&rgb, LAST_FRAME_DELAY)?;
+        }
+        self.encoder.into_inner()?;
+        Ok(())
+    }
+
+    fn write_frame(&mut self, rgb: &[u8], delay: Duration) -> Result<()> {
+        let (image_width, image_height) = self.style.image_size(self.width, self.height);
+        let mut frame =
+            gif::Frame::from_rgb_speed(image_width as u16, image_height as u16, rgb, 10);
+        // GIF delays are in hundredths of a second
+        frame.delay = (delay.as_millis() / 10).clamp(1, u16::MAX as u128) as u16;
+        self.encoder.write_frame(&frame)?;
+        Ok(())
+    }
+}
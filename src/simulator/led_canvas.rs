@@ -1,62 +1,6 @@
-use std::{
-    path::Path,
-    sync::{Arc, Mutex},
-};
+use std::sync::{Arc, Mutex};
 
-use super::LedColor;
-
-pub(crate) struct LedFont {
-    pub(crate) font: bdf::Font,
-}
-
-impl LedFont {
-    pub fn new(bdf_file: &Path) -> Result<Self, &'static str> {
-        let font = bdf::open(bdf_file).map_err(|_| "Failed to open BDF font file")?;
-        Ok(Self { font })
-    }
-}
-
-pub(crate) trait LedCanvasTrait {
-    /// Retrieves the width & height of the canvas
-    #[must_use]
-    fn canvas_size(&self) -> (i32, i32);
-
-    /// Sets the pixel at the given coordinate to the given color.
-    fn set(&mut self, x: i32, y: i32, color: &LedColor);
-
-    /// Clears the canvas.
-    fn clear(&mut self);
-
-    /// Fills the canvas with the given color.
-    fn fill(&mut self, color: &LedColor);
-
-    /// Draws a straight, one pixel wide line using the C++ library.
-    ///
-    /// Consider using embedded-graphics for more drawing features.
-    fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: &LedColor);
-
-    /// Draws a one pixel wide circle using the C++ library.
-    ///
-    /// Consider using embedded-graphics for more drawing features.
-    fn draw_circle(&mut self, x: i32, y: i32, radius: u32, color: &LedColor);
-
-    #[allow(clippy::too_many_arguments)]
-    /// Renders text using the C++ library.
-    ///
-    /// # Panics
-    /// If the given `text` fails to convert to a `CString`. This can
-    /// occur when there is a null character mid way in the string.
-    fn draw_text(
-        &mut self,
-        font: &LedFont,
-        text: &str,
-        x: i32,
-        y: i32,
-        color: &LedColor,
-        kerning_offset: i32,
-        vertical: bool,
-    );
-}
+use crate::led::{font::GlyphBitmap, paint::Brush, Display, LedColor, TextOverflow};
 
 pub(crate) struct LedCanvas {
     pub(crate) width: u32,
@@ -87,7 +31,7 @@ impl LedCanvas {
     }
 }
 
-impl LedCanvasTrait for LedCanvas {
+impl Display for LedCanvas {
     fn fill(&mut self, color: &LedColor) {
         for pixel in &mut self.pixel_buffer.lock().unwrap().iter_mut() {
             *pixel = (*color).into();
@@ -106,101 +50,54 @@ impl LedCanvasTrait for LedCanvas {
         self.pixel_buffer.lock().unwrap()[index] = (*color).into();
     }
 
-    fn clear(&mut self) {
-        self.fill(&LedColor::zero())
-    }
-
-    fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: &LedColor) {
-        // Bresenham's line algorithm
-        let mut x0 = x0;
-        let mut y0 = y0;
-        let x1 = x1;
-        let y1 = y1;
-        let dx = (x1 - x0).abs();
-        let sx = if x0 < x1 { 1 } else { -1 };
-        let dy = -(y1 - y0).abs();
-        let sy = if y0 < y1 { 1 } else { -1 };
-        let mut err = dx + dy;
-        loop {
-            self.set(x0, y0, color);
-            if x0 == x1 && y0 == y1 {
-                break;
-            }
-            let e2 = 2 * err;
-            if e2 >= dy {
-                err += dy;
-                x0 += sx;
-            }
-            if e2 <= dx {
-                err += dx;
-                y0 += sy;
-            }
-        }
-    }
-
-    fn draw_circle(&mut self, x: i32, y: i32, radius: u32, color: &LedColor) {
-        let mut x0 = 0;
-        let mut y0 = radius as i32;
-        let mut d = 3 - 2 * radius as i32;
-        while x0 <= y0 {
-            self.set(x + x0, y + y0, color);
-            self.set(x + x0, y - y0, color);
-            self.set(x - x0, y + y0, color);
-            self.set(x - x0, y - y0, color);
-            self.set(x + y0, y + x0, color);
-            self.set(x + y0, y - x0, color);
-            self.set(x - y0, y + x0, color);
-            self.set(x - y0, y - x0, color);
-            if d < 0 {
-                d += 4 * x0 + 6;
-            } else {
-                d += 4 * (x0 - y0) + 10;
-                y0 -= 1;
-            }
-            x0 += 1;
+    fn get(&self, x: i32, y: i32) -> Option<LedColor> {
+        if x < 0 || x >= self.width as i32 || y < 0 || y >= self.height as i32 {
+            return None;
         }
+        let index = (y * self.width as i32 + x) as usize;
+        Some(self.pixel_buffer.lock().unwrap()[index].into())
     }
 
-    fn draw_text(
+    /// Blits the glyph row by row, locking the pixel buffer once.
+    fn draw_bitmap(
         &mut self,
-        font: &LedFont,
-        text: &str,
-        mut start_x: i32,
-        mut start_y: i32,
-        color: &LedColor,
-        mut kerning_offset: i32,
-        vertical: bool,
+        glyph: &GlyphBitmap,
+        x: i32,
+        y: i32,
+        brush: &Brush,
+        overflow: TextOverflow,
     ) {
-        kerning_offset = std::cmp::max(0, kerning_offset);
-
-        for c in text.chars() {
-            let glyph = font.font.glyphs().get(&c).unwrap();
-            let pixels = glyph.pixels();
-            for ((x, y), draw) in pixels {
-                if draw {
-                    if vertical {
-                        self.set(
-                            (x as i32 + start_x) % self.width as i32,
-                            (y as i32 + start_y) % self.height as i32,
-                            color,
-                        );
-                    } else {
-                        self.set(
-                            (x as i32 + start_x) % self.width as i32,
-                            (y as i32 + start_y) % self.height as i32,
-                            color,
-                        );
-                    }
-                }
-            }
-
-            if vertical {
-                start_y += glyph.height() as i32 + kerning_offset;
-                start_y %= self.height as i32;
-            } else {
-                start_x += glyph.width() as i32 + kerning_offset;
-                start_x %= self.width as i32;
+        let (width, height) = (self.width as i32, self.height as i32);
+        let mut pixels = self.pixel_buffer.lock().unwrap();
+
+        for row in 0..glyph.height {
+            let brush_y = y + row as i32;
+            let y = match overflow {
+                TextOverflow::Clip if !(0..height).contains(&brush_y) => continue,
+                TextOverflow::Clip => brush_y,
+                TextOverflow::Wrap => brush_y.rem_euclid(height),
+            };
+            let line = &mut pixels[(y * width) as usize..((y + 1) * width) as usize];
+            for column in glyph.columns(row) {
+                let brush_x = x + column as i32;
+                let x = match overflow {
+                    TextOverflow::Clip if !(0..width).contains(&brush_x) => continue,
+                    TextOverflow::Clip => brush_x,
+                    TextOverflow::Wrap => brush_x.rem_euclid(width),
+                };
+                let color = brush.color(brush_x, brush_y);
+                let pixel = &mut line[x as usize];
+                *pixel = match glyph.alpha(column, row) {
+                    255 => color.into(),
+                    alpha => LedColor::from(*pixel)
+                        .blend(color, alpha as f32 / 255.0)
+                        .into(),
+                };
             }
         }
     }
+
+    fn swap(&mut self) {
+        // A bare canvas is never on display, frames are presented through `LedMatrix::swap`
+    }
 }
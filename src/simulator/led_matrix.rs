@@ -1,11 +1,25 @@
+use std::sync::{Arc, Mutex};
+
 use super::{
     led_canvas::LedCanvas, led_matrix_options::LedMatrixOptions,
-    led_runtime_options::LedRuntimeOptions,
+    led_runtime_options::LedRuntimeOptions, pixel_mapper::PixelMap,
 };
 
 pub(crate) struct LedMatrix {
     pub(crate) options: LedMatrixOptions,
     pub(crate) _runtime_options: LedRuntimeOptions,
+    /// Bit offsets of the color channels driving the red, green and blue LEDs
+    channel_shifts: [u32; 3],
+    /// Arranges the canvas on the chained and parallel panels
+    pixel_map: PixelMap,
+    /// The frame currently on display, only ever replaced as a whole by [`LedMatrix::swap`].
+    ///
+    /// Holds the panels as they are chained: `cols * chain_length` wide and
+    /// `rows * parallel` high, with the pixel mappers applied.
+    pub(crate) pixel_buffer: Arc<Mutex<Vec<u32>>>,
+    /// The frame on display as it was drawn, before it was presented and mapped, handed back
+    /// by the next [`LedMatrix::swap`]
+    shown: Mutex<Vec<u32>>,
 }
 
 impl LedMatrix {
@@ -15,14 +29,175 @@ impl LedMatrix {
     ) -> Result<Self, String> {
         let options = options.unwrap_or(LedMatrixOptions::new());
         let _runtime_options = _runtime_options.unwrap_or(LedRuntimeOptions::new());
+        let channel_shifts = channel_shifts(&options.led_rgb_sequence)?;
+
+        // Chained panels extend the matrix to the right, parallel chains extend it downwards
+        let matrix_width = options.cols * options.chain_length;
+        let matrix_height = options.rows * options.parallel;
+        let pixel_map = PixelMap::new(
+            &options.pixel_mapper_config,
+            options.chain_length,
+            options.parallel,
+            matrix_width,
+            matrix_height,
+        )?;
+        let pixel_buffer = Arc::new(Mutex::new(vec![0; (matrix_width * matrix_height) as usize]));
+        let shown = Mutex::new(vec![0; (pixel_map.width * pixel_map.height) as usize]);
 
         Ok(Self {
             options,
             _runtime_options,
+            channel_shifts,
+            pixel_map,
+            pixel_buffer,
+            shown,
         })
     }
 
+    /// Creates a canvas with the size of the display after the pixel mappers are applied.
     pub fn offscreen_canvas(&self) -> LedCanvas {
-        LedCanvas::new(self.options.rows, self.options.cols)
+        LedCanvas::new(self.pixel_map.height, self.pixel_map.width)
+    }
+
+    /// Displays the given canvas and returns the previously displayed one for the next frame.
+    ///
+    /// The frame is presented and mapped into a separate display buffer, which is replaced
+    /// as a whole so readers never see a partially drawn frame. Like rpi-led-matrix, the
+    /// returned canvas holds the frame displayed before, exactly as it was drawn.
+    pub fn swap(&self, canvas: LedCanvas) -> LedCanvas {
+        {
+            let mut frame = canvas.pixel_buffer.lock().unwrap();
+            let mut displayed = self.pixel_map.to_matrix(&frame);
+            self.present(&mut displayed);
+            *self.pixel_buffer.lock().unwrap() = displayed;
+            std::mem::swap(&mut *self.shown.lock().unwrap(), &mut *frame);
+        }
+        canvas
+    }
+
+    /// Converts a frame into the colors the panel shows, applying the brightness,
+    /// RGB sequence and inverse colors options.
+    fn present(&self, frame: &mut [u32]) {
+        let brightness = self.options.brightness as u32;
+        for pixel in frame {
+            let mut presented = 0;
+            for (led_shift, channel_shift) in [16, 8, 0].into_iter().zip(self.channel_shifts) {
+                let mut value = ((*pixel >> channel_shift) & 0xFF) * brightness / 100;
+                if self.options.inverse_colors {
+                    value = 0xFF - value;
+                }
+                presented |= value << led_shift;
+            }
+            *pixel = presented;
+        }
+    }
+}
+
+/// Parses an LED RGB sequence like `"RBG"` into the color channel shown by each LED,
+/// as a standard RGB panel would show it.
+fn channel_shifts(sequence: &str) -> Result<[u32; 3], String> {
+    let sequence_upper = sequence.to_ascii_uppercase();
+    if sequence_upper.len() != 3 || !"RGB".chars().all(|c| sequence_upper.contains(c)) {
+        return Err(format!(
+            "Invalid LED RGB sequence {:?}, expected an ordering of R, G and B",
+            sequence
+        ));
+    }
+
+    let mut shifts = [0; 3];
+    for (shift, channel) in shifts.iter_mut().zip(sequence_upper.chars()) {
+        *shift = match channel {
+            'R' => 16,
+            'G' => 8,
+            _ => 0,
+        };
+    }
+    Ok(shifts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LedMatrix;
+    use crate::{
+        led::Display,
+        led_color,
+        simulator::{led_canvas::LedCanvas, LedMatrixOptions},
+    };
+
+    fn pixel(canvas: &LedCanvas, x: i32, y: i32) -> u32 {
+        canvas.get(x, y).unwrap().into()
+    }
+
+    #[test]
+    fn swap_presents_frames_like_the_panel() {
+        let mut options = LedMatrixOptions::new();
+        options.set_rows(16);
+        options.set_cols(32);
+        options.set_chain_length(2);
+        options.set_parallel(3);
+        options.set_brightness(50).unwrap();
+        options.set_led_rgb_sequence("bgr");
+        options.set_inverse_colors(true);
+        let matrix = LedMatrix::new(Some(options), None).unwrap();
+
+        let mut canvas = matrix.offscreen_canvas();
+        assert_eq!(canvas.canvas_size(), (64, 48));
+
+        canvas.set(63, 47, &led_color!("#FF8000"));
+        matrix.swap(canvas);
+
+        let displayed = matrix.pixel_buffer.lock().unwrap();
+        assert_eq!(displayed[64 * 48 - 1], 0xFFBF80);
+        assert_eq!(displayed[0], 0xFFFFFF);
+    }
+
+    #[test]
+    fn swap_applies_the_pixel_mappers() {
+        // Four 32x32 panels in a chain, folded into a 64x64 square
+        let mut options = LedMatrixOptions::new();
+        options.set_chain_length(4);
+        options.set_pixel_mapper_config("U-mapper");
+        let matrix = LedMatrix::new(Some(options), None).unwrap();
+
+        let mut canvas = matrix.offscreen_canvas();
+        assert_eq!(canvas.canvas_size(), (64, 64));
+
+        canvas.set(0, 0, &led_color!("#FFFFFF"));
+        canvas.set(0, 63, &led_color!("#FF0000"));
+        matrix.swap(canvas);
+
+        let displayed = matrix.pixel_buffer.lock().unwrap();
+        assert_eq!(displayed.len(), 128 * 32);
+        assert_eq!(displayed[64], 0xFFFFFF);
+        assert_eq!(displayed[63], 0xFF0000);
+    }
+
+    #[test]
+    fn swapped_back_canvases_keep_their_frame() {
+        let mut options = LedMatrixOptions::new();
+        options.set_chain_length(4);
+        options.set_pixel_mapper_config("U-mapper");
+        options.set_brightness(50).unwrap();
+        options.set_led_rgb_sequence("BGR");
+        options.set_inverse_colors(true);
+        let matrix = LedMatrix::new(Some(options), None).unwrap();
+
+        let mut canvas = matrix.offscreen_canvas();
+        canvas.set(0, 63, &led_color!("#FF8000"));
+        let canvas = matrix.swap(canvas);
+        // The other canvas was never drawn on
+        assert_eq!(pixel(&canvas, 0, 63), 0x000000);
+        let canvas = matrix.swap(canvas);
+
+        assert_eq!(pixel(&canvas, 0, 63), 0xFF8000);
+        assert_eq!(pixel(&canvas, 0, 0), 0x000000);
+    }
+
+    #[test]
+    fn invalid_rgb_sequence_is_rejected() {
+        let mut options = LedMatrixOptions::new();
+        options.set_led_rgb_sequence("RGR");
+
+        assert!(LedMatrix::new(Some(options), None).is_err());
     }
 }
@@ -1,15 +1,43 @@
+/// Mirror of `rpi_led_matrix::LedMatrixOptions`, so the panel is set up the same way on both
+/// backends.
+///
+/// The simulator honors the panel geometry (rows, cols, chain length & parallel chains),
+/// brightness, RGB sequence and inverse colors. The remaining options only matter to the
+/// hardware and are stored without effect.
 pub(crate) struct LedMatrixOptions {
     pub(crate) rows: u32,
     pub(crate) cols: u32,
     pub(crate) hardware_mapping: String,
+    pub(crate) chain_length: u32,
+    pub(crate) parallel: u32,
+    pub(crate) pwm_bits: u8,
+    pub(crate) brightness: u8,
+    pub(crate) scan_mode: u32,
+    pub(crate) led_rgb_sequence: String,
+    pub(crate) pixel_mapper_config: String,
+    pub(crate) row_addr_type: u32,
+    pub(crate) multiplexing: u32,
+    pub(crate) inverse_colors: bool,
 }
 
+type LedMatrixOptionsResult = Result<(), &'static str>;
+
 impl LedMatrixOptions {
     pub fn new() -> Self {
         Self {
             rows: 32,
             cols: 32,
             hardware_mapping: "regular".to_string(),
+            chain_length: 1,
+            parallel: 1,
+            pwm_bits: 11,
+            brightness: 100,
+            scan_mode: 0,
+            led_rgb_sequence: "RGB".to_string(),
+            pixel_mapper_config: String::new(),
+            row_addr_type: 0,
+            multiplexing: 0,
+            inverse_colors: false,
         }
     }
 
@@ -24,4 +52,70 @@ impl LedMatrixOptions {
     pub fn set_hardware_mapping(&mut self, hardware_mapping: &str) {
         self.hardware_mapping = hardware_mapping.to_string();
     }
+
+    /// Sets the number of panels daisy-chained together, which widens the canvas.
+    pub fn set_chain_length(&mut self, chain_length: u32) {
+        self.chain_length = chain_length;
+    }
+
+    /// Sets the number of parallel chains, which heightens the canvas.
+    pub fn set_parallel(&mut self, parallel: u32) {
+        self.parallel = parallel;
+    }
+
+    /// Sets the number of PWM bits to use. Valid range: \[0,11\].
+    ///
+    /// # Errors
+    /// If the given `pwm_bits` is outside the valid range
+    pub fn set_pwm_bits(&mut self, pwm_bits: u8) -> LedMatrixOptionsResult {
+        if pwm_bits > 11 {
+            Err("Pwm bits can only have value between 0 and 11 inclusive")
+        } else {
+            self.pwm_bits = pwm_bits;
+            Ok(())
+        }
+    }
+
+    /// Sets the panel brightness in percent.
+    ///
+    /// # Errors
+    /// If the given `brightness` is not in the range \[1,100\].
+    pub fn set_brightness(&mut self, brightness: u8) -> LedMatrixOptionsResult {
+        if (1..=100).contains(&brightness) {
+            self.brightness = brightness;
+            Ok(())
+        } else {
+            Err("Brightness can only have value between 1 and 100 inclusive")
+        }
+    }
+
+    /// Sets the scan mode. 0: progressive, 1: interlaced.
+    pub fn set_scan_mode(&mut self, scan_mode: u32) {
+        self.scan_mode = scan_mode;
+    }
+
+    /// Sets the ordering of the LEDs on your panel, e.g. `"RBG"`.
+    pub fn set_led_rgb_sequence(&mut self, sequence: &str) {
+        self.led_rgb_sequence = sequence.to_string();
+    }
+
+    /// Semicolon-separated list of pixel-mappers to arrange pixels (e.g. "U-mapper;Rotate:90").
+    pub fn set_pixel_mapper_config(&mut self, mapper: &str) {
+        self.pixel_mapper_config = mapper.to_string();
+    }
+
+    /// If set, invert the color displayed.
+    pub fn set_inverse_colors(&mut self, enable: bool) {
+        self.inverse_colors = enable;
+    }
+
+    /// Sets the type of multiplexing used.
+    pub fn set_multiplexing(&mut self, multiplexing: u32) {
+        self.multiplexing = multiplexing;
+    }
+
+    /// Sets the type of row addressing to be used.
+    pub fn set_row_addr_type(&mut self, row_addr_type: u32) {
+        self.row_addr_type = row_addr_type;
+    }
 }
@@ -1,15 +1,23 @@
+/// Mirror of `rpi_led_matrix::LedRuntimeOptions`. None of these affect the simulator.
 pub(crate) struct LedRuntimeOptions {
     pub(crate) gpio_slowdown: u32,
+    pub(crate) drop_privileges: bool,
 }
 
 impl LedRuntimeOptions {
     pub fn new() -> Self {
         Self {
             gpio_slowdown: 1,
+            drop_privileges: true,
         }
     }
 
     pub fn set_gpio_slowdown(&mut self, gpio_slowdown: u32) {
         self.gpio_slowdown = gpio_slowdown;
     }
-}
\ No newline at end of file
+
+    /// If true, drop privileges from 'root' after initializing the hardware.
+    pub fn set_drop_privileges(&mut self, drop_privileges: bool) {
+        self.drop_privileges = drop_privileges;
+    }
+}
@@ -1,10 +1,22 @@
-pub(crate) mod led_color;
+// The canvas, image export and terminal output are also used by the CLI tools on hardware builds
+pub(crate) mod export;
+pub(crate) mod led_canvas;
+#[cfg(not(feature = "hardware"))]
 pub(crate) mod led_matrix;
+#[cfg(not(feature = "hardware"))]
 pub(crate) mod led_matrix_options;
+#[cfg(not(feature = "hardware"))]
 pub(crate) mod led_runtime_options;
-pub(crate) mod led_canvas;
+pub(crate) mod pixel_mapper;
+#[cfg(test)]
+pub(crate) mod snapshot;
+pub(crate) mod terminal;
+#[cfg(feature = "simulator")]
+pub(crate) mod window;
 
-pub(crate) use led_color::LedColor;
+#[cfg(not(feature = "hardware"))]
 pub(crate) use led_matrix::LedMatrix;
+#[cfg(not(feature = "hardware"))]
 pub(crate) use led_matrix_options::LedMatrixOptions;
-pub(crate) use led_runtime_options::LedRuntimeOptions;
\ No newline at end of file
+#[cfg(not(feature = "hardware"))]
+pub(crate) use led_runtime_options::LedRuntimeOptions;
@@ -0,0 +1,211 @@
+//! Pixel mappers of rpi-led-matrix's `pixel_mapper_config`.
+//!
+//! The matrix is the physical chain of panels: `chain_length` panels next to each other,
+//! `parallel` chains below each other. Mappers turn it into the visible canvas widgets draw
+//! on, e.g. `"U-mapper;Rotate:90"` folds a long chain into a square and rotates it.
+
+/// A single mapper of a `pixel_mapper_config`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PixelMapper {
+    /// `Rotate:<angle>`, clockwise in multiples of 90 degrees
+    Rotate(u32),
+    /// `U-mapper`, folds the chain in half so its second half runs back below the first
+    UMapper { parallel: u32 },
+    /// `Mirror:H` or `Mirror:V`
+    Mirror { horizontal: bool },
+}
+
+impl PixelMapper {
+    /// Parses one mapper like `Rotate:90`, names are case-insensitive.
+    fn parse(mapper: &str, chain_length: u32, parallel: u32) -> Result<Self, String> {
+        let (name, parameter) = mapper.split_once(':').unwrap_or((mapper, ""));
+
+        match name.to_ascii_lowercase().as_str() {
+            "rotate" => {
+                let angle = if parameter.is_empty() {
+                    0
+                } else {
+                    parameter
+                        .parse::<i32>()
+                        .map_err(|_| format!("Rotate: invalid angle {:?}", parameter))?
+                };
+                if angle % 90 != 0 {
+                    return Err(format!("Rotate: angle {} is not a multiple of 90", angle));
+                }
+                Ok(Self::Rotate(angle.rem_euclid(360) as u32))
+            }
+            "u-mapper" => {
+                if chain_length < 2 || !chain_length.is_multiple_of(2) {
+                    return Err(format!(
+                        "U-mapper: chain length {} needs to be divisible by two",
+                        chain_length
+                    ));
+                }
+                Ok(Self::UMapper { parallel })
+            }
+            "mirror" => match parameter.to_ascii_lowercase().as_str() {
+                "" | "h" => Ok(Self::Mirror { horizontal: true }),
+                "v" => Ok(Self::Mirror { horizontal: false }),
+                _ => Err(format!(
+                    "Mirror: expected H or V as direction, found {:?}",
+                    parameter
+                )),
+            },
+            _ => Err(format!("Unsupported pixel mapper {:?}", name)),
+        }
+    }
+
+    /// Size of the visible canvas this mapper creates from a matrix of the given size.
+    fn visible_size(&self, matrix_width: u32, matrix_height: u32) -> Result<(u32, u32), String> {
+        match *self {
+            Self::Rotate(90 | 270) => Ok((matrix_height, matrix_width)),
+            Self::Rotate(_) | Self::Mirror { .. } => Ok((matrix_width, matrix_height)),
+            Self::UMapper { parallel } => {
+                if !matrix_height.is_multiple_of(parallel) {
+                    return Err(format!(
+                        "U-mapper: height {} is not divisible by {} parallel chains",
+                        matrix_height, parallel
+                    ));
+                }
+                Ok((matrix_width / 2, matrix_height * 2))
+            }
+        }
+    }
+
+    /// Maps a visible coordinate to the matrix coordinate it is shown at.
+    fn map(&self, matrix_width: u32, matrix_height: u32, x: u32, y: u32) -> (u32, u32) {
+        match *self {
+            Self::Rotate(90) => (matrix_width - y - 1, x),
+            Self::Rotate(180) => (matrix_width - x - 1, matrix_height - y - 1),
+            Self::Rotate(270) => (y, matrix_height - x - 1),
+            Self::Rotate(_) => (x, y),
+            Self::Mirror { horizontal: true } => (matrix_width - x - 1, y),
+            Self::Mirror { horizontal: false } => (x, matrix_height - y - 1),
+            Self::UMapper { parallel } => {
+                let panel_height = matrix_height / parallel;
+                let visible_width = matrix_width / 2;
+                // Every parallel chain is folded into a slab of two panel rows
+                let slab_height = 2 * panel_height;
+                let base_y = (y / slab_height) * panel_height;
+                let y = y % slab_height;
+                if y < panel_height {
+                    (x + matrix_width / 2, base_y + y)
+                } else {
+                    (visible_width - x - 1, base_y + slab_height - y - 1)
+                }
+            }
+        }
+    }
+}
+
+/// Lookup table from every pixel of the visible canvas to its pixel on the matrix.
+pub(crate) struct PixelMap {
+    /// Width & height of the visible canvas
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    matrix_indices: Vec<usize>,
+}
+
+impl PixelMap {
+    /// Builds the map for a `pixel_mapper_config` on a matrix of the given size.
+    ///
+    /// # Errors
+    /// If a mapper is unknown, has an invalid parameter or does not fit the matrix.
+    pub(crate) fn new(
+        config: &str,
+        chain_length: u32,
+        parallel: u32,
+        matrix_width: u32,
+        matrix_height: u32,
+    ) -> Result<Self, String> {
+        let mappers = config
+            .split(';')
+            .map(str::trim)
+            .filter(|mapper| !mapper.is_empty())
+            .map(|mapper| PixelMapper::parse(mapper, chain_length, parallel))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Every mapper works on the canvas created by the previous one
+        let mut sizes = vec![(matrix_width, matrix_height)];
+        for mapper in &mappers {
+            let (width, height) = sizes[sizes.len() - 1];
+            sizes.push(mapper.visible_size(width, height)?);
+        }
+        let (width, height) = sizes[mappers.len()];
+
+        let mut matrix_indices = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let (matrix_x, matrix_y) = mappers
+                    .iter()
+                    .zip(&sizes)
+                    .rev()
+                    .fold((x, y), |(x, y), (mapper, &(width, height))| {
+                        mapper.map(width, height, x, y)
+                    });
+                matrix_indices.push((matrix_y * matrix_width + matrix_x) as usize);
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            matrix_indices,
+        })
+    }
+
+    /// Rearranges a frame of the visible canvas into the matrix layout.
+    pub(crate) fn to_matrix(&self, frame: &[u32]) -> Vec<u32> {
+        let mut matrix = vec![0; frame.len()];
+        for (&pixel, &index) in frame.iter().zip(&self.matrix_indices) {
+            matrix[index] = pixel;
+        }
+        matrix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PixelMap;
+
+    /// Maps a frame numbering every visible pixel, so the result shows where each one ends up.
+    fn map_indices(map: &PixelMap) -> Vec<u32> {
+        let frame = (0..map.width * map.height).collect::<Vec<_>>();
+        map.to_matrix(&frame)
+    }
+
+    #[test]
+    fn rotate_swaps_width_and_height() {
+        let map = PixelMap::new("Rotate:90", 1, 1, 3, 2).unwrap();
+
+        assert_eq!((map.width, map.height), (2, 3));
+        // Visible rows become matrix columns from right to left
+        assert_eq!(map_indices(&map), [4, 2, 0, 5, 3, 1]);
+    }
+
+    #[test]
+    fn u_mapper_folds_the_chain() {
+        // Four 2x1 panels in a chain, folded into a 4x2 canvas
+        let map = PixelMap::new("U-mapper", 4, 1, 8, 1).unwrap();
+
+        assert_eq!((map.width, map.height), (4, 2));
+        // The top row is shown by the second half of the chain, the bottom row runs back
+        assert_eq!(map_indices(&map), [7, 6, 5, 4, 0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn mappers_are_applied_in_order() {
+        let map = PixelMap::new("Mirror:V; rotate:-90", 1, 1, 2, 2).unwrap();
+
+        assert_eq!((map.width, map.height), (2, 2));
+        assert_eq!(map_indices(&map), [0, 2, 1, 3]);
+    }
+
+    #[test]
+    fn invalid_configs_are_rejected() {
+        assert!(PixelMap::new("Rotate:45", 1, 1, 64, 64).is_err());
+        assert!(PixelMap::new("U-mapper", 3, 1, 192, 64).is_err());
+        assert!(PixelMap::new("Mirror:X", 1, 1, 64, 64).is_err());
+        assert!(PixelMap::new("V-mapper", 2, 1, 128, 64).is_err());
+    }
+}
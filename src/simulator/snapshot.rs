@@ -0,0 +1,149 @@
+//! Golden-image snapshot testing for widgets.
+//!
+//! A widget is rendered into a headless [`LedCanvas`] and compared pixel by pixel against
+//! `tests/snapshots/<name>.png`. On a mismatch the rendered frame and a visual diff are
+//! written to `target/snapshots/`. Run the tests with `UPDATE_SNAPSHOTS=1` to (re)create
+//! the reference images after an intended layout change.
+
+use std::{
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use super::led_canvas::LedCanvas;
+use crate::led::LedFont;
+
+// Scale of the diff image so single pixel differences are easy to spot
+const DIFF_SCALE: u32 = 8;
+
+/// Asserts that the canvas matches the reference image with the given name.
+///
+/// # Panics
+/// If the reference image is missing or differs from the canvas.
+pub(crate) fn assert_snapshot(name: &str, canvas: &LedCanvas) {
+    let frame = canvas.pixel_buffer.lock().unwrap().clone();
+    let reference_path = snapshot_dir().join(format!("{}.png", name));
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(snapshot_dir()).unwrap();
+        write_png(&reference_path, &frame, canvas.width, canvas.height, 1);
+        return;
+    }
+
+    let Some((reference, width, height)) = read_png(&reference_path) else {
+        panic!(
+            "Missing reference image {}, run the tests with UPDATE_SNAPSHOTS=1 to create it",
+            reference_path.display()
+        );
+    };
+    assert_eq!(
+        (width, height),
+        (canvas.width, canvas.height),
+        "Snapshot {} has a different size than the canvas",
+        name
+    );
+
+    let mismatches = frame
+        .iter()
+        .zip(&reference)
+        .filter(|(actual, expected)| actual != expected)
+        .count();
+    if mismatches == 0 {
+        return;
+    }
+
+    fs::create_dir_all(output_dir()).unwrap();
+    let actual_path = output_dir().join(format!("{}.actual.png", name));
+    let diff_path = output_dir().join(format!("{}.diff.png", name));
+    write_png(&actual_path, &frame, width, height, 1);
+    write_png(
+        &diff_path,
+        &diff(&frame, &reference),
+        width,
+        height,
+        DIFF_SCALE,
+    );
+
+    panic!(
+        "Snapshot {} differs in {} pixel(s)\n  reference: {}\n  actual:    {}\n  diff:      {}",
+        name,
+        mismatches,
+        reference_path.display(),
+        actual_path.display(),
+        diff_path.display()
+    );
+}
+
+/// Directory of the fonts shipped with the repository.
+pub(crate) fn fonts_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("fonts")
+}
+
+/// Loads a bitmap font shipped with the repository, e.g. `5x8.bdf`.
+pub(crate) fn font(name: &str) -> LedFont {
+    LedFont::new(&fonts_dir().join(name)).unwrap()
+}
+
+fn snapshot_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots")
+}
+
+fn output_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("target/snapshots")
+}
+
+/// Marks differing pixels red and dims matching ones, so the layout stays recognisable.
+fn diff(actual: &[u32], expected: &[u32]) -> Vec<u32> {
+    actual
+        .iter()
+        .zip(expected)
+        .map(|(actual, expected)| {
+            if actual == expected {
+                (actual >> 2) & 0x3F3F3F
+            } else {
+                0xFF0000
+            }
+        })
+        .collect()
+}
+
+fn read_png(path: &Path) -> Option<(Vec<u32>, u32, u32)> {
+    let decoder = png::Decoder::new(File::open(path).ok()?);
+    let mut reader = decoder.read_info().unwrap();
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).unwrap();
+    assert_eq!(
+        (info.color_type, info.bit_depth),
+        (png::ColorType::Rgb, png::BitDepth::Eight),
+        "Snapshot {} must be an 8-bit RGB image",
+        path.display()
+    );
+
+    let pixels = buffer[..info.buffer_size()]
+        .chunks_exact(3)
+        .map(|rgb| (rgb[0] as u32) << 16 | (rgb[1] as u32) << 8 | rgb[2] as u32)
+        .collect();
+    Some((pixels, info.width, info.height))
+}
+
+fn write_png(path: &Path, frame: &[u32], width: u32, height: u32, scale: u32) {
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(File::create(path).unwrap()),
+        width * scale,
+        height * scale,
+    );
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut rgb = Vec::with_capacity((width * height * scale * scale * 3) as usize);
+    for y in 0..height * scale {
+        for x in 0..width * scale {
+            let pixel = frame[((y / scale) * width + x / scale) as usize];
+            rgb.extend_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8]);
+        }
+    }
+
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&rgb).unwrap();
+}
@@ -0,0 +1,93 @@
+use std::fmt::Write as _;
+#[cfg(not(feature = "hardware"))]
+use std::{
+    io::Write as _,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+// Upper half block: the foreground color paints the top LED, the background the bottom one
+const HALF_BLOCK: char = '▀';
+
+/// Draws the simulator pixel buffer into the terminal every `refresh_rate`, redrawing in place.
+///
+/// Requires a terminal with 24-bit color support. Every text row shows two rows of LEDs.
+#[cfg(not(feature = "hardware"))]
+pub(crate) fn start_terminal_polling(
+    pixel_buffer: Arc<Mutex<Vec<u32>>>,
+    width: u32,
+    height: u32,
+    refresh_rate: Duration,
+) {
+    std::thread::spawn(move || {
+        // Clear once, later frames overwrite the previous one from the top-left corner
+        print!("\x1b[2J");
+        loop {
+            let frame = pixel_buffer.lock().unwrap().clone();
+            let output = format!("\x1b[H{}", render_frame(&frame, width, height));
+
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(output.as_bytes()).unwrap();
+            stdout.flush().unwrap();
+            drop(stdout);
+
+            std::thread::sleep(refresh_rate);
+        }
+    });
+}
+
+/// Renders a `0xRRGGBB` frame as lines of ANSI truecolor half blocks.
+pub(crate) fn render_frame(frame: &[u32], width: u32, height: u32) -> String {
+    let mut output = String::new();
+
+    for y in (0..height).step_by(2) {
+        // Only emit escape codes when a color changes, which keeps the output small
+        let mut current = None;
+        for x in 0..width {
+            let top = frame[(y * width + x) as usize];
+            let bottom = if y + 1 < height {
+                frame[((y + 1) * width + x) as usize]
+            } else {
+                0
+            };
+
+            if current != Some((top, bottom)) {
+                write!(
+                    output,
+                    "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m",
+                    (top >> 16) & 0xFF,
+                    (top >> 8) & 0xFF,
+                    top & 0xFF,
+                    (bottom >> 16) & 0xFF,
+                    (bottom >> 8) & 0xFF,
+                    bottom & 0xFF,
+                )
+                .unwrap();
+                current = Some((top, bottom));
+            }
+            output.push(HALF_BLOCK);
+        }
+        output.push_str("\x1b[0m\n");
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render_frame;
+
+    #[test]
+    fn render_frame_pairs_rows_into_half_blocks() {
+        let frame = [0xFF0000, 0xFF0000, 0x00FF00, 0x0000FF, 0xFFFFFF, 0xFFFFFF];
+
+        let output = render_frame(&frame, 2, 3);
+
+        assert_eq!(
+            output,
+            "\x1b[38;2;255;0;0m\x1b[48;2;0;255;0m▀\
+             \x1b[38;2;255;0;0m\x1b[48;2;0;0;255m▀\x1b[0m\n\
+             \x1b[38;2;255;255;255m\x1b[48;2;0;0;0m▀▀\x1b[0m\n"
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use minifb::{Key, KeyRepeat, Scale, Window, WindowOptions};
+
+use super::{
+    export::{ExportStyle, GifRecorder},
+    led_canvas::LedCanvas,
+};
+use crate::config::WindowScale;
+
+const TITLE: &str = "LED matrix";
+
+// Screenshots and recordings are meant for PRs and documentation, so render them like the panel
+const EXPORT_STYLE: ExportStyle = ExportStyle {
+    scale: 8,
+    led_dots: true,
+};
+
+// Input is polled much faster than the panel refreshes so key presses are never missed
+const WINDOW_UPDATE_RATE: Duration = Duration::from_micros(16_600);
+
+/// Shows the simulator pixel buffer in a window until it is closed.
+///
+/// Must be run on the main thread, minifb cannot create a window from another thread.
+///
+/// Keyboard shortcuts:
+/// - `Esc` / `Q`: quit
+/// - `P` / `Space`: pause or resume the display
+/// - `S`: save a PNG screenshot of the current frame
+/// - `R`: start or stop recording an animated GIF
+pub(crate) fn start_window_polling(
+    pixel_buffer: Arc<Mutex<Vec<u32>>>,
+    width: u32,
+    height: u32,
+    scale: WindowScale,
+) {
+    let mut window = Window::new(
+        TITLE,
+        width as usize,
+        height as usize,
+        WindowOptions {
+            resize: false,
+            scale: match scale {
+                WindowScale::Fit => Scale::FitScreen,
+                WindowScale::Factor(1) => Scale::X1,
+                WindowScale::Factor(2) => Scale::X2,
+                WindowScale::Factor(4) => Scale::X4,
+                WindowScale::Factor(8) => Scale::X8,
+                WindowScale::Factor(32) => Scale::X32,
+                WindowScale::Factor(_) => Scale::X16,
+            },
+            ..WindowOptions::default()
+        },
+    )
+    .expect("Failed to create window.");
+    window.limit_update_rate(Some(WINDOW_UPDATE_RATE));
+
+    // Copy of the displayed frame, kept while paused and used for exports
+    let frame = LedCanvas::new(height, width);
+    let mut recorder: Option<GifRecorder> = None;
+    let mut paused = false;
+
+    while window.is_open() {
+        if window.is_key_pressed(Key::Escape, KeyRepeat::No)
+            || window.is_key_pressed(Key::Q, KeyRepeat::No)
+        {
+            break;
+        }
+
+        if window.is_key_pressed(Key::P, KeyRepeat::No)
+            || window.is_key_pressed(Key::Space, KeyRepeat::No)
+        {
+            paused = !paused;
+        }
+
+        if window.is_key_pressed(Key::R, KeyRepeat::No) {
+            match recorder.take() {
+                Some(recording) => stop_recording(recording),
+                None => recorder = start_recording(&frame),
+            }
+        }
+
+        if !paused {
+            let changed = {
+                let displayed = pixel_buffer.lock().unwrap();
+                let mut buffer = frame.pixel_buffer.lock().unwrap();
+                let changed = *buffer != *displayed;
+                buffer.copy_from_slice(&displayed);
+                changed
+            };
+
+            if changed {
+                if let Some(recording) = recorder.as_mut() {
+                    if let Err(e) = recording.push_frame(&frame) {
+                        eprintln!("Failed to record frame: {}", e);
+                        recorder = None;
+                    }
+                }
+            }
+        }
+
+        if window.is_key_pressed(Key::S, KeyRepeat::No) {
+            let path = export_path("screenshot", "png");
+            match frame.save_png(Path::new(&path), EXPORT_STYLE) {
+                Ok(()) => println!("Saved screenshot to {}", path),
+                Err(e) => eprintln!("Failed to save screenshot: {}", e),
+            }
+        }
+
+        window.set_title(&match (paused, recorder.is_some()) {
+            (false, false) => TITLE.to_string(),
+            (true, false) => format!("{} (paused)", TITLE),
+            (false, true) => format!("{} (recording)", TITLE),
+            (true, true) => format!("{} (paused, recording)", TITLE),
+        });
+
+        window
+            .update_with_buffer(
+                &frame.pixel_buffer.lock().unwrap(),
+                width as usize,
+                height as usize,
+            )
+            .unwrap();
+    }
+
+    if let Some(recording) = recorder {
+        stop_recording(recording);
+    }
+}
+
+fn export_path(prefix: &str, extension: &str) -> String {
+    format!(
+        "{}-{}.{}",
+        prefix,
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        extension
+    )
+}
+
+fn start_recording(frame: &LedCanvas) -> Option<GifRecorder> {
+    let path = export_path("recording", "gif");
+    let recording = GifRecorder::new(Path::new(&path), frame.width, frame.height, EXPORT_STYLE)
+        .and_then(|mut recording| recording.push_frame(frame).map(|_| recording));
+
+    match recording {
+        Ok(recording) => {
+            println!("Recording to {}", path);
+            Some(recording)
+        }
+        Err(e) => {
+            eprintln!("Failed to start recording: {}", e);
+            None
+        }
+    }
+}
+
+fn stop_recording(recording: GifRecorder) {
+    match recording.finish() {
+        Ok(()) => println!("Recording saved"),
+        Err(e) => eprintln!("Failed to save recording: {}", e),
+    }
+}
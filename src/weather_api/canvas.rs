@@ -1,111 +1,121 @@
-#[cfg(target_os = "linux")]
-use rpi_led_matrix::{
-    LedCanvas, LedColor, LedFont
-};
+use std::time::Duration;
 
-#[cfg(any(target_os = "windows", target_os = "macos"))]
-use crate::simulator::{
-    led_canvas::{LedCanvas, LedCanvasTrait, LedFont},
-    LedColor,
-};
+use crate::led::{marquee::Marquee, Display, LedColor, LedFont, TextPaint};
 
 use super::WeatherCondition;
 use super::{api::WeatherApiResponse, bitmaps, weather_code_bitmap};
 
-pub(crate) fn draw_weather(
-    canvas: &mut LedCanvas,
-    font: &LedFont,
-    weather: &WeatherApiResponse,
-    x: usize,
-    y: usize,
-    color: &LedColor,
-) {
-    let x = x as i32;
-    let y = y as i32;
-
-    canvas.draw_text(
-        &font,
-        format!(
-            "{}{}",
-            weather.current.temperature_2m, weather.current_units.temperature_2m
-        )
-        .as_str(),
-        x,
-        y,
-        color,
-        0,
-        false,
-    );
+/// Width & height of the weather condition icons
+const ICON_SIZE: i32 = 15;
+/// Width & height of the wind direction arrows
+const ARROW_SIZE: i32 = 9;
+/// Pixels between two lines of text
+const LINE_SPACING: i32 = 2;
 
-    canvas.draw_text(
-        &font,
-        format!(
-            "{}{}",
-            weather.current.relative_humidity_2m, weather.current_units.relative_humidity_2m
-        )
-        .as_str(),
-        x,
-        y + 10,
-        color,
-        0,
-        false,
-    );
+/// The current temperature, humidity & wind speed one below the other, with the weather
+/// condition icon and the wind direction in a column along the right edge.
+///
+/// Lines are spaced from the font height and scroll when wider than the space left of the
+/// icons.
+pub(crate) struct WeatherWidget {
+    /// Temperature, humidity & wind speed
+    lines: [Marquee; 3],
+    /// Top-left corner of the weather condition icon
+    icon: (i32, i32),
+    /// Top-left corner of the wind direction arrow
+    arrow: (i32, i32),
+}
 
-    draw_weather_image(canvas, x + 49, y, weather.current.weather_code.into());
-    draw_wind_direction(
-        canvas,
-        x + 49,
-        y + 18,
-        weather.current.wind_direction_10m as i32,
-    );
-    canvas.draw_text(
-        &font,
-        format!(
-            "{}{}",
-            weather.current.wind_speed_10m, weather.current_units.wind_speed_10m
-        )
-        .as_str(),
-        x,
-        y + 20,
-        color,
-        0,
-        false,
-    );
+impl WeatherWidget {
+    /// Lays out the widget starting at `(x, y)` and spanning `width` pixels.
+    pub(crate) fn new(font: &LedFont, x: i32, y: i32, width: u32) -> Self {
+        let line_height = font.ascent() + font.descent();
+        let line_top = |line: i32| y + line * (line_height + LINE_SPACING);
+        let icon_x = x + width as i32 - ICON_SIZE;
+        let text_width = (icon_x - x - LINE_SPACING).max(0) as u32;
+        let line = |index| Marquee::new("", x, line_top(index), text_width, line_height as u32);
+
+        Self {
+            lines: [line(0), line(1), line(2)],
+            icon: (icon_x, y),
+            // The arrow sits below the icon, next to the wind speed
+            arrow: (
+                icon_x + (ICON_SIZE - ARROW_SIZE) / 2,
+                line_top(2) + (line_height - ARROW_SIZE) / 2,
+            ),
+        }
+    }
+
+    /// Draws the weather, `elapsed` being the time the animations are timed from.
+    pub(crate) fn draw<D: Display>(
+        &mut self,
+        canvas: &mut D,
+        font: &LedFont,
+        weather: &WeatherApiResponse,
+        paint: &TextPaint,
+        elapsed: Duration,
+    ) {
+        let texts = [
+            format!(
+                "{}{}",
+                weather.current.temperature_2m, weather.current_units.temperature_2m
+            ),
+            format!(
+                "{}{}",
+                weather.current.relative_humidity_2m, weather.current_units.relative_humidity_2m
+            ),
+            format!(
+                "{}{}",
+                weather.current.wind_speed_10m, weather.current_units.wind_speed_10m
+            ),
+        ];
+        for (line, text) in self.lines.iter_mut().zip(&texts) {
+            line.set_text(text, elapsed);
+            line.draw(canvas, font, paint, elapsed);
+        }
+
+        let (x, y) = self.icon;
+        draw_weather_image(canvas, x, y, weather.current.weather_code.into());
+        let (x, y) = self.arrow;
+        draw_wind_direction(canvas, x, y, weather.current.wind_direction_10m as i32);
+    }
 }
 
-fn draw_weather_image(canvas: &mut LedCanvas, x: i32, y: i32, weather_code: WeatherCondition) {
-    let image_width = 15i32;
-    draw_pixels(
-        canvas,
-        x,
-        y,
-        image_width,
-        weather_code_bitmap(weather_code.into()),
-    );
+fn draw_weather_image<D: Display>(canvas: &mut D, x: i32, y: i32, weather_code: WeatherCondition) {
+    draw_pixels(canvas, x, y, ICON_SIZE, weather_code_bitmap(weather_code));
 }
 
-fn draw_wind_direction(canvas: &mut LedCanvas, x: i32, y: i32, angle: i32) {
-    let image_width = 9i32;
+fn draw_wind_direction<D: Display>(canvas: &mut D, x: i32, y: i32, angle: i32) {
     draw_pixels(
         canvas,
         x,
         y,
-        image_width,
+        ARROW_SIZE,
         get_wind_direction_from_angle(angle),
     );
 }
 
-fn draw_pixels(canvas: &mut LedCanvas, mut x: i32, mut y: i32, width: i32, pixels: &'static [u32]) {
+fn draw_pixels<D: Display>(
+    canvas: &mut D,
+    mut x: i32,
+    mut y: i32,
+    width: i32,
+    pixels: &'static [u32],
+) {
     let max_x = x + width;
     for pixel in pixels {
-        canvas.set(x, y, &(LedColor { 
-            red: (*pixel >> 16) as u8,
-            green: (*pixel >> 8) as u8,
-            blue: *pixel as u8
-        }));
+        canvas.set(
+            x,
+            y,
+            &(LedColor {
+                red: (*pixel >> 16) as u8,
+                green: (*pixel >> 8) as u8,
+                blue: *pixel as u8,
+            }),
+        );
         x += 1;
         if x == max_x {
-            x = x - width;
+            x -= width;
             y += 1;
         }
     }
@@ -129,3 +139,35 @@ fn get_wind_direction_from_angle(angle: i32) -> &'static [u32] {
         _ => &bitmaps::WIND_DIRECTIONS_UNKNOWN,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::WeatherWidget;
+    use crate::{
+        led_color,
+        simulator::{
+            led_canvas::LedCanvas,
+            snapshot::{assert_snapshot, font},
+        },
+        weather_api::api::{WeatherApiResponse, MOCK_RESPONSE},
+    };
+
+    #[test]
+    fn weather_widget_matches_snapshot() {
+        let font = font("5x8.bdf");
+        let weather: WeatherApiResponse = serde_json::from_str(MOCK_RESPONSE).unwrap();
+        let mut canvas = LedCanvas::new(32, 64);
+
+        WeatherWidget::new(&font, 1, 1, 62).draw(
+            &mut canvas,
+            &font,
+            &weather,
+            &led_color!("#2EC866").into(),
+            Duration::ZERO,
+        );
+
+        assert_snapshot("weather", &canvas);
+    }
+}
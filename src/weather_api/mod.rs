@@ -1,4 +1,6 @@
 pub(crate) mod api;
+// Generated pixel data, one icon per line
+#[rustfmt::skip]
 pub(crate) mod bitmaps;
 pub(crate) mod canvas;
 